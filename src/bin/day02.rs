@@ -1,51 +1,362 @@
-use aoc25::read_csv;
+use aoc25::{merge_ranges, read_csv_radix};
 
 // Day 2
 // Output: Solution -> Part1: 18700015741 || Part2: 20077272987
+// Flags: --per-range  sum each input range on its own (overlaps are counted once per range)
+//        --repeats=K / --at-least=K / --period=P  query IDs by repetition (filters combine)
+//        --list  stream every ID matching the query (needs a filter)
+//        --radix=R  read the ranges and check IDs in base R (2-36, default 10)
+//        --report  print what each input range contributed before the solution
+//          --csv  print the report as CSV instead of text
+//          --first=N  number of offending IDs listed per range in the report (default 5)
 fn main() {
     let input = include_str!("../../inputs/day02.txt");
 
-    let arr: Vec<(i64, i64)> = read_csv(input);
+    let args: Vec<String> = std::env::args().collect();
+    let radix = parse_radix(&args);
+    let filters = parse_filters(&args);
+    let list = args.iter().any(|a| a == "--list");
+    assert!(
+        !list || !filters.is_empty(),
+        "--list needs --repeats, --at-least or --period"
+    );
 
-    let (p1, p2) = solve(&arr);
+    let arr: Vec<(u128, u128)> = read_csv_radix(input, radix);
+
+    let mode = if args.iter().any(|a| a == "--per-range") {
+        SumMode::PerRange
+    } else {
+        SumMode::Union
+    };
+
+    // Report per original range, before any merging
+    if args.iter().any(|a| a == "--report") {
+        let first_n = args
+            .iter()
+            .find_map(|a| a.strip_prefix("--first="))
+            .map_or(5, |n| n.parse().expect("--first must be a number"));
+        let reports: Vec<RangeReport> = arr
+            .iter()
+            .map(|&(x, y)| report_range(x, y, radix, first_n))
+            .collect::<Result<_, _>>()
+            .unwrap_or_else(|e| overflow_exit(e, radix));
+        if args.iter().any(|a| a == "--csv") {
+            print_report_csv(&reports, radix);
+        } else {
+            print_report_text(&reports, radix);
+        }
+    }
+
+    let (p1, p2) = solve(&arr, mode, radix).unwrap_or_else(|e| overflow_exit(e, radix));
     println!("Solution -> Part1: {} || Part2: {}", p1, p2);
+
+    if !filters.is_empty() {
+        let ranges = match mode {
+            SumMode::PerRange => arr.clone(),
+            SumMode::Union => merge_ranges(&arr),
+        };
+
+        // One pass, listing the IDs as they're counted
+        let q = query(&ranges, &filters, radix, |id| {
+            if list {
+                println!("{}", fmt_radix(id, radix));
+            }
+        })
+        .unwrap_or_else(|e| overflow_exit(e, radix));
+        println!(
+            "Query {:?} -> Count: {} || Sum: {}",
+            filters, q.count, q.sum
+        );
+    }
+}
+
+// How the totals are built from the input ranges
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum SumMode {
+    // Sum every range as given, an ID in two overlapping ranges is counted twice
+    PerRange,
+    // Merge overlapping/adjacent ranges first so every ID is counted once
+    Union,
+}
+
+// A sum of IDs went past u128::MAX while adding in the range start..=end
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct SumOverflow {
+    start: u128,
+    end: u128,
 }
 
 // Complexity: O(n*m) -> O(n) where n is the number of pairs and m is the size of the digits in the pairs
-fn solve(input: &[(i64, i64)]) -> (i64, i64) {
-    let mut sum1 = 0;
-    let mut sum2 = 0;
-    for &(x, y) in input {
-        let mut i = x;
-        while i < y + 1 {
-            let is_invalid_id_1 = is_invalid_id_p1(i);
-            if is_invalid_id_1 {
-                sum1 += i
-            }
-            let is_invalid_id_2 = is_invalid_id_p2(i);
-            if is_invalid_id_2 {
-                sum2 += i
+// Union mode adds an O(n log n) sort to merge the ranges first
+fn solve(input: &[(u128, u128)], mode: SumMode, radix: u32) -> Result<(u128, u128), SumOverflow> {
+    let ranges = match mode {
+        SumMode::PerRange => input.to_vec(),
+        SumMode::Union => merge_ranges(input),
+    };
+
+    ranges
+        .iter()
+        .try_fold((0u128, 0u128), |(sum1, sum2), &(x, y)| {
+            let (s1, s2) = sum_range(x, y, radix)?;
+            let overflow = SumOverflow { start: x, end: y };
+            Ok((
+                sum1.checked_add(s1).ok_or(overflow)?,
+                sum2.checked_add(s2).ok_or(overflow)?,
+            ))
+        })
+}
+
+// Sum the invalid IDs in x..=y for both parts
+fn sum_range(x: u128, y: u128, radix: u32) -> Result<(u128, u128), SumOverflow> {
+    let report = report_range(x, y, radix, 0)?;
+    Ok((report.sum1, report.sum2))
+}
+
+// Sums are written in decimal, the range in the input radix
+fn overflow_exit(e: SumOverflow, radix: u32) -> ! {
+    eprintln!(
+        "{}-{}: sum of IDs overflows u128",
+        fmt_radix(e.start, radix),
+        fmt_radix(e.end, radix)
+    );
+    std::process::exit(1);
+}
+
+// What a single input range contributed under both rules
+#[derive(Debug)]
+struct RangeReport {
+    start: u128,
+    end: u128,
+    count1: usize,
+    sum1: u128,
+    count2: usize,
+    sum2: u128,
+    // First offending IDs in the range, every part 1 invalid ID is also part 2 invalid so these
+    // are the IDs invalid under either rule
+    first: Vec<u128>,
+}
+
+// Walk x..=y once, counting and summing invalid IDs and keeping the first `first_n` of them
+fn report_range(x: u128, y: u128, radix: u32, first_n: usize) -> Result<RangeReport, SumOverflow> {
+    let overflow = SumOverflow { start: x, end: y };
+    let mut report = RangeReport {
+        start: x,
+        end: y,
+        count1: 0,
+        sum1: 0,
+        count2: 0,
+        sum2: 0,
+        first: Vec::new(),
+    };
+
+    for i in x..=y {
+        if is_invalid_id_p1(i, radix) {
+            report.count1 += 1;
+            report.sum1 = report.sum1.checked_add(i).ok_or(overflow)?;
+        }
+        if is_invalid_id_p2(i, radix) {
+            report.count2 += 1;
+            report.sum2 = report.sum2.checked_add(i).ok_or(overflow)?;
+            if report.first.len() < first_n {
+                report.first.push(i);
             }
-            i += 1;
         }
     }
-    (sum1, sum2)
+    Ok(report)
+}
+
+// IDs are written in the input radix, counts and sums in decimal
+fn print_report_text(reports: &[RangeReport], radix: u32) {
+    for r in reports {
+        let first: Vec<String> = r.first.iter().map(|&id| fmt_radix(id, radix)).collect();
+        println!(
+            "{}-{} -> Part1: {} IDs, sum {} || Part2: {} IDs, sum {} || First: {}",
+            fmt_radix(r.start, radix),
+            fmt_radix(r.end, radix),
+            r.count1,
+            r.sum1,
+            r.count2,
+            r.sum2,
+            first.join(", ")
+        );
+    }
+}
+
+// Same as the text report, offending IDs are space separated in the last column
+fn print_report_csv(reports: &[RangeReport], radix: u32) {
+    println!("start,end,part1_count,part1_sum,part2_count,part2_sum,first_ids");
+    for r in reports {
+        let first: Vec<String> = r.first.iter().map(|&id| fmt_radix(id, radix)).collect();
+        println!(
+            "{},{},{},{},{},{},{}",
+            fmt_radix(r.start, radix),
+            fmt_radix(r.end, radix),
+            r.count1,
+            r.sum1,
+            r.count2,
+            r.sum2,
+            first.join(" ")
+        );
+    }
 }
 
 // Part 1
 // Split the number into two halves and check if they are the same
-fn is_invalid_id_p1(id: i64) -> bool {
-    let s = &id.to_string();
+fn is_invalid_id_p1(id: u128, radix: u32) -> bool {
+    let s = &fmt_radix(id, radix);
     let (a, b) = s.split_at(s.chars().count() / 2);
     a == b
 }
 
 // Part 2
 // Repeat the number twice, chop off the first and last character if it contains the original number, then it's invalid because there is a repeated pattern.
-fn is_invalid_id_p2(id: i64) -> bool {
-    let s = &id.to_string();
+fn is_invalid_id_p2(id: u128, radix: u32) -> bool {
+    let s = &fmt_radix(id, radix);
     let rep = String::from(s).repeat(2);
     let chopped = &rep[1..rep.len() - 1];
 
     chopped.contains(s)
 }
+
+// Minimal period of an ID's digits and how many times that period repeats
+// e.g. 123123 -> period 3, repeats 2 and 1111 -> period 1, repeats 4
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+struct Repetition {
+    period: usize,
+    repeats: usize,
+}
+
+// Properties an ID can be filtered on, by its minimal period
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum RepeatFilter {
+    // Minimal period repeats exactly k times
+    Exactly(usize),
+    // Minimal period repeats at least k times
+    AtLeast(usize),
+    // Minimal period is exactly p digits long
+    Period(usize),
+}
+
+impl RepeatFilter {
+    fn matches(&self, rep: Repetition) -> bool {
+        match *self {
+            RepeatFilter::Exactly(k) => rep.repeats == k,
+            RepeatFilter::AtLeast(k) => rep.repeats >= k,
+            RepeatFilter::Period(p) => rep.period == p,
+        }
+    }
+}
+
+// Result of a range query
+#[derive(Debug, Default)]
+struct RangeQuery {
+    count: usize,
+    sum: u128,
+}
+
+// Classify an ID using the KMP prefix function over its digits
+// The smallest period is n - pi[n - 1], which only tiles the string if it divides n
+// Complexity: O(m), m = number of digits
+fn classify_id(id: u128, radix: u32) -> Repetition {
+    let s = fmt_radix(id, radix).into_bytes();
+    let n = s.len();
+
+    let mut pi = vec![0; n];
+    for i in 1..n {
+        let mut k = pi[i - 1];
+        while k > 0 && s[i] != s[k] {
+            k = pi[k - 1];
+        }
+        if s[i] == s[k] {
+            k += 1;
+        }
+        pi[i] = k;
+    }
+
+    let p = n - pi[n - 1];
+    let period = if n.is_multiple_of(p) { p } else { n };
+    Repetition {
+        period,
+        repeats: n / period,
+    }
+}
+
+// Lazily yields every ID in the ranges that matches all filters
+fn matching_ids<'a>(
+    ranges: &'a [(u128, u128)],
+    filters: &'a [RepeatFilter],
+    radix: u32,
+) -> impl Iterator<Item = u128> + 'a {
+    ranges.iter().flat_map(|&(x, y)| x..=y).filter(move |&id| {
+        let rep = classify_id(id, radix);
+        filters.iter().all(|f| f.matches(rep))
+    })
+}
+
+// Collects the count and sum of matching IDs, handing each one to `visit` as it's found
+fn query(
+    ranges: &[(u128, u128)],
+    filters: &[RepeatFilter],
+    radix: u32,
+    mut visit: impl FnMut(u128),
+) -> Result<RangeQuery, SumOverflow> {
+    let mut q = RangeQuery::default();
+    for range in ranges {
+        let overflow = SumOverflow {
+            start: range.0,
+            end: range.1,
+        };
+        for id in matching_ids(std::slice::from_ref(range), filters, radix) {
+            visit(id);
+            q.count += 1;
+            q.sum = q.sum.checked_add(id).ok_or(overflow)?;
+        }
+    }
+    Ok(q)
+}
+
+// Reads --repeats=K, --at-least=K and --period=P from the command line
+fn parse_filters(args: &[String]) -> Vec<RepeatFilter> {
+    let mut filters = Vec::new();
+    for arg in args {
+        let Some((name, value)) = arg.split_once('=') else {
+            continue;
+        };
+        let filter: fn(usize) -> RepeatFilter = match name {
+            "--repeats" => RepeatFilter::Exactly,
+            "--at-least" => RepeatFilter::AtLeast,
+            "--period" => RepeatFilter::Period,
+            _ => continue,
+        };
+        filters.push(filter(
+            value.parse().expect("filter value must be a number"),
+        ));
+    }
+    filters
+}
+
+// Reads --radix=R from the command line, defaulting to base 10
+fn parse_radix(args: &[String]) -> u32 {
+    let radix = args
+        .iter()
+        .find_map(|a| a.strip_prefix("--radix="))
+        .map_or(10, |r| r.parse().expect("radix must be a number"));
+    assert!((2..=36).contains(&radix), "radix must be between 2 and 36");
+    radix
+}
+
+// Writes an ID out in the given radix, lowercase letters for digits above 9
+// Complexity: O(m), m = number of digits
+fn fmt_radix(mut id: u128, radix: u32) -> String {
+    if id == 0 {
+        return String::from("0");
+    }
+
+    let mut digits = Vec::new();
+    while id > 0 {
+        let d = (id % radix as u128) as u32;
+        digits.push(char::from_digit(d, radix).unwrap());
+        id /= radix as u128;
+    }
+    digits.iter().rev().collect()
+}
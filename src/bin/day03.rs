@@ -1,75 +1,509 @@
 use aoc25::read_lines;
+use std::fmt;
 
 // Day 3
 // Output: Solution -> Part1: 117524 || Part2: 173848577117276
+// Flags: --show[=ansi]  print each bank with the chosen batteries in [brackets] or ANSI colour
+//        --k=N  number of batteries chosen for --show (default 12)
+//        --short=skip|pad|error  what to do with banks shorter than k (default pad)
+//        --objective=max|min|max-non-adjacent|max-digit-sum  what the selection optimises (default max)
 fn main() {
     let input = include_str!("../../inputs/day03.txt");
 
     let arr: Vec<&str> = read_lines(input).collect();
 
-    let (p1, p2) = solve(&arr);
+    let args: Vec<String> = std::env::args().collect();
+    let policy = args.iter().find_map(|a| a.strip_prefix("--short=")).map_or(
+        ShortPolicy::Pad,
+        |p| match p {
+            "skip" => ShortPolicy::Skip,
+            "pad" => ShortPolicy::Pad,
+            "error" => ShortPolicy::Error,
+            _ => panic!("--short must be one of skip, pad or error"),
+        },
+    );
+
+    let objective = args
+        .iter()
+        .find_map(|a| a.strip_prefix("--objective="))
+        .map_or(Objective::Max, |o| match o {
+            "max" => Objective::Max,
+            "min" => Objective::Min,
+            "max-non-adjacent" => Objective::MaxNonAdjacent,
+            "max-digit-sum" => Objective::MaxDigitSum,
+            _ => panic!("--objective must be one of max, min, max-non-adjacent or max-digit-sum"),
+        });
+
+    let highlight = args.iter().find_map(|a| match a.as_str() {
+        "--show" => Some(Highlight::Brackets),
+        "--show=ansi" => Some(Highlight::Ansi),
+        _ => None,
+    });
+    if let Some(highlight) = highlight {
+        let k = args
+            .iter()
+            .find_map(|a| a.strip_prefix("--k="))
+            .map_or(12, |k| k.parse().expect("--k must be a number"));
+        for line in &arr {
+            if let Ok(Some(selection)) = select_checked(line, k, policy, objective) {
+                println!(
+                    "{} -> {}",
+                    render_selection(line, &selection, highlight),
+                    selection.value
+                );
+            }
+        }
+    }
+
+    let ((p1, p2), errors) = solve(&arr, policy, objective);
+    for (line, err) in &errors {
+        eprintln!("line {}: {}", line, err);
+    }
     println!("Solution -> Part1: {} || Part2: {}", p1, p2);
 }
 
-fn solve(input: &[&str]) -> (i32, i64) {
+// Invalid or rejected banks are left out of the sums and returned with their 1-based line number
+fn solve(
+    input: &[&str],
+    policy: ShortPolicy,
+    objective: Objective,
+) -> ((u128, u128), Vec<(usize, BankError)>) {
     let mut sum = 0;
     let mut sum2 = 0;
-    for line in input {
-        sum += find_joltage(line);
-        sum2 += find_joltage_n(line);
+    let mut errors = Vec::new();
+
+    for (i, line) in input.iter().enumerate() {
+        // Report bad characters once per line rather than once per part
+        if let Err(err) = validate_bank(line) {
+            errors.push((i + 1, err));
+            continue;
+        }
+
+        match find_joltage(line, policy, objective) {
+            Ok(value) => sum += value.unwrap_or(0),
+            Err(err) => errors.push((i + 1, err)),
+        }
+        match find_joltage_n(line, policy, objective) {
+            Ok(value) => sum2 += value.unwrap_or(0),
+            Err(err) => errors.push((i + 1, err)),
+        }
     }
-    (sum, sum2)
+    ((sum, sum2), errors)
 }
 
 // Part 1
-// Easy solution since only two batteries are required
-// Find max in first set which is all but last character
-// Find max in second set which is all characters after the first max
-// Complexity: O(n) where n is the number of batteries
-fn find_joltage(input: &str) -> i32 {
-    let chars: Vec<char> = input.chars().collect();
+// Only two batteries are required, which is the k = 2 case of `select_checked`
+// Ok(None) means the bank was skipped by the short bank policy
+fn find_joltage(
+    input: &str,
+    policy: ShortPolicy,
+    objective: Objective,
+) -> Result<Option<u128>, BankError> {
+    Ok(select_checked(input, 2, policy, objective)?.map(|s| s.value.parse().unwrap()))
+}
+
+// Part 2
+// Same as part 1 but with 12 batteries
+fn find_joltage_n(
+    input: &str,
+    policy: ShortPolicy,
+    objective: Objective,
+) -> Result<Option<u128>, BankError> {
+    Ok(select_checked(input, 12, policy, objective)?.map(|s| s.value.parse().unwrap()))
+}
 
-    let first_digit = chars[..chars.len() - 1].iter().max().unwrap();
-    let first_digit_idx = chars.iter().position(|&c| c == *first_digit).unwrap();
+// Why a bank couldn't be used
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum BankError {
+    // Blank line where a bank was expected
+    Empty,
+    // Every (position, character) in the bank that isn't 0-9
+    NonDigit(Vec<(usize, char)>),
+    // Fewer than k batteries with the error policy
+    TooShort { len: usize, k: usize },
+}
 
-    let second_digit = chars[first_digit_idx + 1..].iter().max().unwrap();
+impl fmt::Display for BankError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            BankError::Empty => write!(f, "empty bank"),
+            BankError::NonDigit(bad) => {
+                let list: Vec<String> = bad
+                    .iter()
+                    .map(|(i, c)| format!("{:?} at {}", c, i))
+                    .collect();
+                write!(f, "non-digit characters: {}", list.join(", "))
+            }
+            BankError::TooShort { len, k } => {
+                write!(f, "bank has {} batteries but {} are required", len, k)
+            }
+        }
+    }
+}
 
-    format!("{}{}", first_digit, second_digit).parse().unwrap()
+// What to do with a bank that has fewer than k batteries
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ShortPolicy {
+    // Leave the bank out
+    Skip,
+    // Switch on every battery, the value is the bank read as a number (as if left-padded with 0s)
+    Pad,
+    // Reject the bank with `BankError::TooShort`
+    Error,
 }
 
-// Part 2
-// Slightly more diffcult solution since n batteries are required
-// Loop with start = the last max found + 1, and rem = n - number of max found
-// Keep doing this until no digits remain, in effect this is until the end of the string is reached
-// Complexity: O(n*m) -> O(n) where n is the # of batteries and m is the # of digits to find
-fn find_joltage_n(input: &str) -> i64 {
-    let chars: Vec<char> = input.chars().collect();
+// Checks that the bank isn't blank and only holds digits, positions are 0-based character offsets
+// A blank bank is an error under every short policy
+fn validate_bank(bank: &str) -> Result<(), BankError> {
+    if bank.is_empty() {
+        return Err(BankError::Empty);
+    }
+
+    let bad: Vec<(usize, char)> = bank
+        .chars()
+        .enumerate()
+        .filter(|(_, c)| !c.is_ascii_digit())
+        .collect();
+
+    if bad.is_empty() {
+        Ok(())
+    } else {
+        Err(BankError::NonDigit(bad))
+    }
+}
+
+// Validates the bank then selects k batteries, applying the policy if the bank is too short
+fn select_checked(
+    bank: &str,
+    k: usize,
+    policy: ShortPolicy,
+    objective: Objective,
+) -> Result<Option<Selection>, BankError> {
+    validate_bank(bank)?;
+
+    let len = bank.len();
+    if objective.capacity(len) >= k {
+        return Ok(Some(select_by(bank, k, objective)));
+    }
+
+    match policy {
+        ShortPolicy::Skip => Ok(None),
+        ShortPolicy::Pad => {
+            let mut selection = select_by(bank, k, objective);
+            selection.value = format!("{:0>width$}", selection.value, width = k);
+            Ok(Some(selection))
+        }
+        ShortPolicy::Error => Err(BankError::TooShort { len, k }),
+    }
+}
+
+// Batteries switched on in a bank, `indices` are ascending positions into the bank
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Selection {
+    indices: Vec<usize>,
+    value: String,
+}
+
+// What a selection of k batteries optimises
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Objective {
+    // Largest k-digit number (the puzzle)
+    Max,
+    // Smallest k-digit number without a leading zero
+    Min,
+    // Largest k-digit number where no two chosen batteries are next to each other
+    MaxNonAdjacent,
+    // Largest digit sum, ties broken by the larger number
+    MaxDigitSum,
+}
+
+impl Objective {
+    // Most batteries that can be chosen from a bank of `len` under this objective
+    fn capacity(&self, len: usize) -> usize {
+        match self {
+            Objective::MaxNonAdjacent => len.div_ceil(2),
+            _ => len,
+        }
+    }
+}
+
+// Select k batteries for the objective, if the bank can't fit k then as many as possible are chosen
+fn select_by(bank: &str, k: usize, objective: Objective) -> Selection {
+    let k = k.min(objective.capacity(bank.len()));
+    let indices = match objective {
+        Objective::Max => return select_batteries(bank, k),
+        Objective::Min => select_min(bank.as_bytes(), k),
+        Objective::MaxNonAdjacent => select_max_non_adjacent(bank.as_bytes(), k),
+        Objective::MaxDigitSum => select_max_digit_sum(bank.as_bytes(), k),
+    };
+
+    let digits = bank.as_bytes();
+    let value = indices.iter().map(|&i| digits[i] as char).collect();
+    Selection { indices, value }
+}
+
+// Min
+// The first digit is the smallest non-zero digit that still leaves k - 1 digits after it, taking the
+// earliest one so the most digits are left. The rest is the mirror of the max monotonic stack, popping
+// while the top is larger. If every candidate for the first digit is 0 then the 0 can't be avoided
+// Complexity: O(n)
+fn select_min(digits: &[u8], k: usize) -> Vec<usize> {
+    if k == 0 {
+        return Vec::new();
+    }
+
+    let window = &digits[..=digits.len() - k];
+    let first = window
+        .iter()
+        .enumerate()
+        .filter(|&(_, &d)| d != b'0')
+        .min_by_key(|&(i, &d)| (d, i))
+        .map_or(0, |(i, _)| i);
+
+    let mut drops = digits.len() - first - k;
+    let mut stack: Vec<usize> = vec![first];
+
+    for i in first + 1..digits.len() {
+        while drops > 0 && stack.len() > 1 && digits[*stack.last().unwrap()] > digits[i] {
+            stack.pop();
+            drops -= 1;
+        }
+        stack.push(i);
+    }
+
+    stack.truncate(k);
+    stack
+}
 
-    if chars.len() <= 12 {
-        return input.parse().unwrap();
+// Max with no two adjacent batteries
+// Greedy: each pick is the largest digit (earliest on ties) in the window of positions that still
+// leaves room for the remaining picks. After a pick at i, j picks need 2j - 1 more positions from i + 2,
+// so the window for a pick with `rem` picks left (including itself) ends at n - 2 * rem + 1.
+// Both window ends only move right, so a sliding-window max (monotonic deque) answers each pick
+// Complexity: O(n)
+fn select_max_non_adjacent(digits: &[u8], k: usize) -> Vec<usize> {
+    let n = digits.len();
+    let mut picked = Vec::with_capacity(k);
+    let mut window: std::collections::VecDeque<usize> = std::collections::VecDeque::new();
+    let mut next = 0; // Next position to add to the window
+
+    for rem in (1..=k).rev() {
+        let end = if rem == 1 { n - 1 } else { n + 1 - 2 * rem };
+
+        while next <= end {
+            // Strictly smaller so the earliest of equal digits stays at the front
+            while window.back().is_some_and(|&b| digits[b] < digits[next]) {
+                window.pop_back();
+            }
+            window.push_back(next);
+            next += 1;
+        }
+
+        let i = window.pop_front().unwrap();
+        picked.push(i);
+
+        // Next pick must be at least two positions on
+        while window.front().is_some_and(|&f| f < i + 2) {
+            window.pop_front();
+        }
+        next = next.max(i + 2);
+    }
+
+    picked
+}
+
+// Max digit sum
+// The sum is fixed by taking the k largest digits. Only the smallest chosen digit t can have a
+// choice of positions, and taking the latest copies of t gives the largest number: moving a t later
+// shifts larger-or-equal digits forward
+// Complexity: O(n)
+fn select_max_digit_sum(digits: &[u8], k: usize) -> Vec<usize> {
+    let mut counts = [0; 10];
+    for &d in digits {
+        counts[(d - b'0') as usize] += 1;
+    }
+
+    // Find the threshold digit t and how many copies of it are needed
+    let mut need = k;
+    let mut t = 9;
+    while need > counts[t] {
+        need -= counts[t];
+        t -= 1;
+    }
+    let t = b'0' + t as u8;
+
+    let mut take_t = vec![false; digits.len()];
+    for (i, &d) in digits.iter().enumerate().rev() {
+        if need == 0 {
+            break;
+        }
+        if d == t {
+            take_t[i] = true;
+            need -= 1;
+        }
+    }
+
+    (0..digits.len())
+        .filter(|&i| digits[i] > t || take_t[i])
+        .collect()
+}
+
+// Monotonic stack: while the top of the stack is smaller than the next digit and we can still
+// afford to drop digits, pop it. The stack stays non-increasing and its first k digits are the answer
+// The stack holds positions so the chosen batteries can be reported, not just the digits
+// If the bank has k digits or fewer then all of them are kept
+// Complexity: O(n) where n is the number of batteries, each digit is pushed and popped at most once
+fn select_batteries(bank: &str, k: usize) -> Selection {
+    let digits = bank.as_bytes();
+    let mut drops = digits.len().saturating_sub(k);
+    let mut stack: Vec<usize> = Vec::with_capacity(digits.len());
+
+    for (i, &d) in digits.iter().enumerate() {
+        while drops > 0 && stack.last().is_some_and(|&top| digits[top] < d) {
+            stack.pop();
+            drops -= 1;
+        }
+        stack.push(i);
     }
 
-    let mut result = String::new();
-    let mut start = 0;
-    let mut rem = 12;
+    stack.truncate(k);
+    let value = stack.iter().map(|&i| digits[i] as char).collect();
+    Selection {
+        indices: stack,
+        value,
+    }
+}
 
-    while rem > 0 {
-        let end = chars.len() - rem;
+// How chosen batteries are marked when rendering a bank
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Highlight {
+    // e.g. 9[8]7[6]
+    Brackets,
+    // Bold green, for terminals
+    Ansi,
+}
 
-        let mut max_dig = '0';
-        let mut max_dig_idx = start;
+// Render the bank with the selected batteries highlighted
+fn render_selection(bank: &str, selection: &Selection, highlight: Highlight) -> String {
+    let mut out = String::with_capacity(bank.len() * 2);
+    let mut chosen = selection.indices.iter().peekable();
 
-        for i in start..=end {
-            if chars[i] > max_dig {
-                max_dig = chars[i];
-                max_dig_idx = i;
+    for (i, ch) in bank.chars().enumerate() {
+        if chosen.next_if_eq(&&i).is_none() {
+            out.push(ch);
+            continue;
+        }
+        match highlight {
+            Highlight::Brackets => {
+                out.push('[');
+                out.push(ch);
+                out.push(']');
+            }
+            Highlight::Ansi => {
+                out.push_str("\x1b[1;32m");
+                out.push(ch);
+                out.push_str("\x1b[0m");
             }
         }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn banks(seed: u64, count: usize) -> Vec<String> {
+        let mut next = aoc25::xorshift(seed);
+
+        (0..count)
+            .map(|_| {
+                let len = 1 + (next() % 11) as usize;
+                // Narrow digit ranges give plenty of ties
+                let spread = [3, 10][(next() % 2) as usize];
+                (0..len)
+                    .map(|_| (b'0' + (next() % spread) as u8) as char)
+                    .collect()
+            })
+            .collect()
+    }
+
+    // Every way of choosing k positions, as ascending indices
+    fn subsets(n: usize, k: usize) -> Vec<Vec<usize>> {
+        (0u32..1 << n)
+            .filter(|mask| mask.count_ones() as usize == k)
+            .map(|mask| (0..n).filter(|&i| mask & (1 << i) != 0).collect())
+            .collect()
+    }
+
+    fn value(bank: &str, indices: &[usize]) -> String {
+        indices
+            .iter()
+            .map(|&i| bank.as_bytes()[i] as char)
+            .collect()
+    }
 
-        result.push(max_dig);
-        start = max_dig_idx + 1;
-        rem -= 1;
+    fn digit_sum(value: &str) -> u32 {
+        value.bytes().map(|d| (d - b'0') as u32).sum()
     }
 
-    result.parse().unwrap()
+    // Best value over every subset, None if no subset is allowed
+    fn brute_force(bank: &str, k: usize, objective: Objective) -> Option<String> {
+        let values = subsets(bank.len(), k).into_iter().filter_map(|s| {
+            let adjacent = s.windows(2).any(|w| w[1] == w[0] + 1);
+            (objective != Objective::MaxNonAdjacent || !adjacent).then(|| value(bank, &s))
+        });
+
+        match objective {
+            Objective::Max | Objective::MaxNonAdjacent => values.max(),
+            Objective::MaxDigitSum => values.max_by_key(|v| (digit_sum(v), v.clone())),
+            Objective::Min => {
+                let values: Vec<String> = values.collect();
+                let no_zero = values.iter().filter(|v| !v.starts_with('0')).min();
+                no_zero.or(values.iter().min()).cloned()
+            }
+        }
+    }
+
+    fn check(objective: Objective, seed: u64) {
+        for bank in banks(seed, 300) {
+            for k in 1..=objective.capacity(bank.len()) {
+                let selection = select_by(&bank, k, objective);
+                let expected = brute_force(&bank, k, objective);
+
+                assert_eq!(
+                    Some(&selection.value),
+                    expected.as_ref(),
+                    "{} k={}",
+                    bank,
+                    k
+                );
+                assert!(selection.indices.windows(2).all(|w| w[0] < w[1]));
+                assert_eq!(value(&bank, &selection.indices), selection.value);
+                if objective == Objective::MaxNonAdjacent {
+                    assert!(selection.indices.windows(2).all(|w| w[1] > w[0] + 1));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn max_matches_brute_force() {
+        check(Objective::Max, 0x9e3779b97f4a7c15);
+    }
+
+    #[test]
+    fn min_matches_brute_force() {
+        check(Objective::Min, 0x2545f4914f6cdd1d);
+    }
+
+    #[test]
+    fn max_non_adjacent_matches_brute_force() {
+        check(Objective::MaxNonAdjacent, 0xd1b54a32d192ed03);
+    }
+
+    #[test]
+    fn max_digit_sum_matches_brute_force() {
+        check(Objective::MaxDigitSum, 0x94d049bb133111eb);
+    }
 }
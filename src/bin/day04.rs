@@ -2,17 +2,100 @@ use aoc25::read_lines;
 
 // Day 4
 // Output: Solution -> Part1: 1491 || Part2: 8722
+// Flags: --rounds  table of how many rolls were removed in each round
+//        --heatmap  grid of the round each roll was removed in (1-9, a-z, then +), @ = never removed
+//        --after=N  grid after N rounds of removal
+//        --neighbourhood=moore|von-neumann|moore:R|custom:DI,DJ;DI,DJ;...  cells counted as neighbours
+//        --occupied=CHARS  characters that count as rolls (default @)
+//        --rule=<4  comparison and threshold for a roll to be removable (<, <=, =, >=, >)
+//        --edge=empty|wall|wrap  what lies past the edge of the grid (default empty)
+//        --bits  use the bit-packed engine (Moore/von Neumann neighbourhoods on rectangular grids)
+//        --check  run the bit-packed and scalar engines and check they agree round by round
+//        --core  the grid left once nothing more can be removed and its clusters of rolls
 fn main() {
     let input = include_str!("../../inputs/day04.txt");
 
     let arr: Vec<&str> = read_lines(input).collect();
 
-    let (p1, p2) = solve(&arr);
+    let args: Vec<String> = std::env::args().collect();
+    let rule = parse_rule(&args);
+
+    if args
+        .iter()
+        .any(|a| a == "--rounds" || a == "--heatmap" || a.starts_with("--after="))
+    {
+        let history = simulate_removal(&arr, &rule);
+
+        if args.iter().any(|a| a == "--rounds") {
+            println!("round,removed,remaining");
+            let mut remaining = history.initial_rolls();
+            for (round, count) in history.round_counts().iter().enumerate() {
+                remaining -= count;
+                println!("{},{},{}", round + 1, count, remaining);
+            }
+        }
+        if args.iter().any(|a| a == "--heatmap") {
+            for row in history.heatmap() {
+                println!("{}", row);
+            }
+        }
+        if let Some(n) = args.iter().find_map(|a| a.strip_prefix("--after=")) {
+            for row in history.grid_after(n.parse().expect("--after must be a number")) {
+                println!("{}", row);
+            }
+        }
+    }
+
+    if args.iter().any(|a| a == "--core") {
+        let history = simulate_removal(&arr, &rule);
+        for row in history.grid_after(history.rounds.len()) {
+            println!("{}", row);
+        }
+
+        let clusters = stable_core(&history, &rule);
+        println!(
+            "Survivors: {} rolls in {} clusters",
+            history.initial_rolls() - history.total(),
+            clusters.len()
+        );
+        for (n, cluster) in clusters.iter().enumerate() {
+            let ((r0, c0), (r1, c1)) = (cluster.top_left, cluster.bottom_right);
+            println!(
+                "Cluster {}: {} rolls, rows {}-{}, cols {}-{}",
+                n + 1,
+                cluster.size,
+                r0,
+                r1,
+                c0,
+                c1
+            );
+        }
+    }
+
+    if args.iter().any(|a| a == "--check") {
+        let scalar = simulate_removal(&arr, &rule).round_counts();
+        let bits = bit_removal(&arr, &rule).expect("rule or grid not supported by the bit engine");
+        assert_eq!(scalar, bits, "bit engine disagrees with the scalar engine");
+        println!("Bit engine matches scalar over {} rounds", bits.len());
+    }
+
+    let (p1, p2) = if args.iter().any(|a| a == "--bits") {
+        solve_bits(&arr, &rule).expect("rule or grid not supported by the bit engine")
+    } else {
+        solve(&arr, &rule)
+    };
     println!("Solution -> Part1: {} || Part2: {}", p1, p2);
 }
 
-fn solve(input: &[&str]) -> (i32, i32) {
-    (find_rolls(input), find_rolls_mod(input))
+fn solve(input: &[&str], rule: &Rule) -> (i32, i32) {
+    (find_rolls(input, rule), find_rolls_mod(input, rule))
+}
+
+// Part 1 is the first removal round, so both parts come from the round counts
+fn solve_bits(input: &[&str], rule: &Rule) -> Option<(i32, i32)> {
+    let rounds = bit_removal(input, rule)?;
+    let p1 = rounds.first().copied().unwrap_or(0);
+    Some((p1 as i32, rounds.iter().sum::<usize>() as i32))
 }
 
 // Relative directions of adjacent cells from existing (x,y) in the grid
@@ -28,44 +111,116 @@ const DIRECTIONS: [(isize, isize); 8] = [
     (1, 1),
 ];
 
-// Part 1
-// Easy solutuon, just look in all DIRECTIONS and find '@'
-// If reached 4, break and sum
-// Complexity: O(n^2) - The operation is O(n*m) where n = number of cols and m = number of rows,
-// since every operation has DIRECTIION checking, the work per iteration results to O(n^2)
-fn find_rolls(input: &[&str]) -> i32 {
-    let mut sum = 0;
+// Which cells around (x,y) count as neighbours
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Neighbourhood {
+    // Up, down, left and right
+    VonNeumann,
+    // All 8 surrounding cells (the puzzle)
+    Moore,
+    // Every cell within r steps in both directions, Moore is radius 1
+    MooreRadius(usize),
+    // Any set of relative offsets, (0, 0) is ignored
+    Custom(Vec<(isize, isize)>),
+}
 
-    for i in 0..input.len() {
-        for j in 0..input[i].len() {
-            if input[i].chars().nth(j) != Some('@') {
-                continue;
+impl Neighbourhood {
+    fn offsets(&self) -> Vec<(isize, isize)> {
+        match self {
+            Neighbourhood::VonNeumann => vec![(-1, 0), (0, -1), (0, 1), (1, 0)],
+            Neighbourhood::Moore => DIRECTIONS.to_vec(),
+            Neighbourhood::MooreRadius(r) => {
+                let r = *r as isize;
+                (-r..=r)
+                    .flat_map(|di| (-r..=r).map(move |dj| (di, dj)))
+                    .filter(|&o| o != (0, 0))
+                    .collect()
             }
+            Neighbourhood::Custom(offsets) => {
+                offsets.iter().copied().filter(|&o| o != (0, 0)).collect()
+            }
+        }
+    }
+}
 
-            let mut found = 0;
+// How a cell's neighbour count is compared against the threshold
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Comparison {
+    Less,
+    LessEq,
+    Equal,
+    GreaterEq,
+    Greater,
+}
 
-            for (di, dj) in &DIRECTIONS {
-                let adj_i = i as isize + *di;
-                let adj_j = j as isize + *dj;
+impl Comparison {
+    fn holds(self, count: usize, threshold: usize) -> bool {
+        match self {
+            Comparison::Less => count < threshold,
+            Comparison::LessEq => count <= threshold,
+            Comparison::Equal => count == threshold,
+            Comparison::GreaterEq => count >= threshold,
+            Comparison::Greater => count > threshold,
+        }
+    }
+}
 
-                if adj_i < 0 || adj_j < 0 {
-                    continue;
-                }
+// When an occupied cell can be removed, the puzzle rule is "an @ with fewer than 4 of its 8
+// neighbours occupied"
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Rule {
+    neighbourhood: Neighbourhood,
+    occupied: Vec<char>,
+    comparison: Comparison,
+    threshold: usize,
+    edge: EdgePolicy,
+}
 
-                if let Some(row) = input.get(adj_i as usize) {
-                    if let Some(ch) = row.chars().nth(adj_j as usize) {
-                        if ch == '@' {
-                            found += 1;
+// What a neighbour outside the grid is treated as
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum EdgePolicy {
+    // Nothing there (the puzzle)
+    Empty,
+    // A wall, counted as occupied but never removed
+    Wall,
+    // The grid is a torus, stepping off one side comes back on the other
+    Wrap,
+}
 
-                            if found >= 4 {
-                                break;
-                            }
-                        }
-                    }
-                }
-            }
+impl Default for Rule {
+    fn default() -> Self {
+        Rule {
+            neighbourhood: Neighbourhood::Moore,
+            occupied: vec!['@'],
+            comparison: Comparison::Less,
+            threshold: 4,
+            edge: EdgePolicy::Empty,
+        }
+    }
+}
+
+impl Rule {
+    fn is_occupied(&self, ch: char) -> bool {
+        self.occupied.contains(&ch)
+    }
+
+    fn removable(&self, count: usize) -> bool {
+        self.comparison.holds(count, self.threshold)
+    }
+}
+
+// Part 1
+// Easy solutuon, just look at every neighbour and count the occupied ones
+// Sum the occupied cells where the count passes the rule
+// Complexity: O(n*k) where n = number of cells and k = size of the neighbourhood (8 for the puzzle)
+fn find_rolls(input: &[&str], rule: &Rule) -> i32 {
+    let grid = occupancy(input, rule);
+    let offsets = rule.neighbourhood.offsets();
+    let mut sum = 0;
 
-            if found < 4 {
+    for i in 0..grid.len() {
+        for j in 0..grid[i].len() {
+            if grid[i][j] && rule.removable(count_neighbours(&grid, rule, &offsets, i, j)) {
                 sum += 1;
             }
         }
@@ -77,57 +232,195 @@ fn find_rolls(input: &[&str]) -> i32 {
 // Part 2
 // Slightly more difficult, the concept is the same but the grid will be modified each iteration to
 // remove adjacent paper rolls and create space.
-// Complexity: O(n^2) similar to Part 1 with work required, this time the space complexity is much
-// higher due to mutable grid being stored.
-fn find_rolls_mod(input: &[&str]) -> i32 {
-    // Create grid from original input that is mutable so we can remove paper rolls
-    let mut grid: Vec<Vec<char>> = input.iter().map(|row| row.chars().collect()).collect();
+// Rather than rescanning the whole grid every round, keep a count of adjacent rolls for every roll.
+// Removing a roll only changes the counts of its neighbours, so only those need checking next round.
+// Rolls are still removed a round at a time using the counts from the start of the round, so the
+// result is the same as rescanning.
+// Complexity: O(n*k) where n is the number of cells and k the size of the neighbourhood, each roll
+// is removed at most once and only touches its neighbours when it is.
+fn find_rolls_mod(input: &[&str], rule: &Rule) -> i32 {
+    simulate_removal(input, rule).total() as i32
+}
 
-    let mut sum = 0;
+// Which cells were removed in which round, rounds[0] is the first round
+struct RemovalHistory {
+    initial: Vec<Vec<char>>,
+    initial_rolls: usize,
+    rounds: Vec<Vec<(usize, usize)>>,
+}
 
-    loop {
-        // What can be removed in this round
-        let mut to_remove: Vec<(usize, usize)> = Vec::new();
+impl RemovalHistory {
+    fn total(&self) -> usize {
+        self.rounds.iter().map(|r| r.len()).sum()
+    }
 
-        for i in 0..grid.len() {
-            for j in 0..grid[i].len() {
-                if grid[i][j] != '@' {
-                    continue; // Only care about paper rolls
-                }
+    fn initial_rolls(&self) -> usize {
+        self.initial_rolls
+    }
 
-                let mut found = 0;
+    fn round_counts(&self) -> Vec<usize> {
+        self.rounds.iter().map(|r| r.len()).collect()
+    }
 
-                // Similar to p1, just checking surroundings
-                for (di, dj) in &DIRECTIONS {
-                    let adj_i = i as isize + *di;
-                    let adj_j = j as isize + *dj;
+    // Round (1-based) each cell was removed in, None if it was never removed
+    fn removal_rounds(&self) -> Vec<Vec<Option<usize>>> {
+        let mut out: Vec<Vec<Option<usize>>> = self
+            .initial
+            .iter()
+            .map(|row| vec![None; row.len()])
+            .collect();
+        for (round, cells) in self.rounds.iter().enumerate() {
+            for &(i, j) in cells {
+                out[i][j] = Some(round + 1);
+            }
+        }
+        out
+    }
 
-                    if adj_i < 0 || adj_j < 0 {
-                        continue;
-                    }
+    // Removal rounds drawn as a grid, rounds past 35 all show as '+'
+    fn heatmap(&self) -> Vec<String> {
+        let rounds = self.removal_rounds();
+        self.initial
+            .iter()
+            .zip(rounds)
+            .map(|(row, row_rounds)| {
+                row.iter()
+                    .zip(row_rounds)
+                    .map(|(&ch, round)| match round {
+                        Some(r) => char::from_digit(r as u32, 36).unwrap_or('+'),
+                        None => ch,
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    // The grid once the first n rounds have been applied
+    fn grid_after(&self, n: usize) -> Vec<String> {
+        let mut grid = self.initial.clone();
+        for cells in self.rounds.iter().take(n) {
+            for &(i, j) in cells {
+                grid[i][j] = '.';
+            }
+        }
+        grid.iter().map(|row| row.iter().collect()).collect()
+    }
+}
 
-                    let (ai, aj) = (adj_i as usize, adj_j as usize);
+// A connected group of rolls that can never be removed, corners are (row, col)
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Cluster {
+    size: usize,
+    top_left: (usize, usize),
+    bottom_right: (usize, usize),
+}
 
-                    // Out of bounds
-                    if ai >= grid.len() || aj >= grid[ai].len() {
-                        continue;
-                    }
+// Splits the rolls left after the final round into clusters, largest first
+// Once removal stops no survivor passes the rule against the other survivors, so for the puzzle
+// rule this is the 4-core of the adjacency graph (every survivor has at least 4 surviving
+// neighbours). Two survivors are connected if either is in the other's neighbourhood
+// Complexity: O(n*k) where n is the number of cells and k the size of the neighbourhood
+fn stable_core(history: &RemovalHistory, rule: &Rule) -> Vec<Cluster> {
+    let rounds = history.removal_rounds();
+    let mut alive: Vec<Vec<bool>> = history
+        .initial
+        .iter()
+        .zip(&rounds)
+        .map(|(row, row_rounds)| {
+            row.iter()
+                .zip(row_rounds)
+                .map(|(&ch, round)| rule.is_occupied(ch) && round.is_none())
+                .collect()
+        })
+        .collect();
+    pad_for_wrap(&mut alive, rule.edge);
 
-                    // Found adjacent within this grid cycle
-                    if grid[ai][aj] == '@' {
-                        found += 1;
+    // Both directions so custom one-sided neighbourhoods still give undirected clusters
+    let mut links = rule.neighbourhood.offsets();
+    links.extend(links.clone().iter().map(|&(di, dj)| (-di, -dj)));
 
-                        if found >= 4 {
-                            break;
-                        }
+    let mut clusters = Vec::new();
+    for i in 0..alive.len() {
+        for j in 0..alive[i].len() {
+            if !alive[i][j] {
+                continue;
+            }
+
+            // Flood fill, clearing cells as they're claimed
+            alive[i][j] = false;
+            let mut stack = vec![(i, j)];
+            let mut cluster = Cluster {
+                size: 0,
+                top_left: (i, j),
+                bottom_right: (i, j),
+            };
+
+            while let Some((ci, cj)) = stack.pop() {
+                cluster.size += 1;
+                cluster.top_left = (cluster.top_left.0.min(ci), cluster.top_left.1.min(cj));
+                cluster.bottom_right = (
+                    cluster.bottom_right.0.max(ci),
+                    cluster.bottom_right.1.max(cj),
+                );
+
+                let next: Vec<(usize, usize)> = neighbours(&alive, rule.edge, &links, ci, cj)
+                    .filter(|&(ai, aj)| alive[ai][aj])
+                    .collect();
+                for (ai, aj) in next {
+                    if alive[ai][aj] {
+                        alive[ai][aj] = false;
+                        stack.push((ai, aj));
                     }
                 }
+            }
 
-                if found < 4 {
-                    to_remove.push((i, j));
-                }
+            clusters.push(cluster);
+        }
+    }
+
+    clusters.sort_by_key(|c| std::cmp::Reverse(c.size));
+    clusters
+}
+
+// Runs the removal rounds until nothing changes and records what went in each round
+fn simulate_removal(input: &[&str], rule: &Rule) -> RemovalHistory {
+    let initial: Vec<Vec<char>> = input.iter().map(|row| row.chars().collect()).collect();
+
+    // Mutable occupancy so we can remove paper rolls
+    let mut grid = occupancy(input, rule);
+    let offsets = rule.neighbourhood.offsets();
+
+    // Cells that see (i, j) as a neighbour are (i, j) minus each offset
+    let watchers: Vec<(isize, isize)> = offsets.iter().map(|&(di, dj)| (-di, -dj)).collect();
+
+    // Number of occupied neighbours for each cell, and the first round's candidates (every roll)
+    let mut counts: Vec<Vec<usize>> = grid.iter().map(|row| vec![0; row.len()]).collect();
+    let mut queued: Vec<Vec<bool>> = grid.iter().map(|row| vec![false; row.len()]).collect();
+    let mut candidates: Vec<(usize, usize)> = Vec::new();
+
+    for i in 0..grid.len() {
+        for j in 0..grid[i].len() {
+            if !grid[i][j] {
+                continue; // Only care about paper rolls
             }
+            counts[i][j] = count_neighbours(&grid, rule, &offsets, i, j);
+            queued[i][j] = true;
+            candidates.push((i, j));
         }
+    }
+
+    let initial_rolls = candidates.len();
+    let mut rounds: Vec<Vec<(usize, usize)>> = Vec::new();
+
+    loop {
+        // What can be removed in this round, decided before anything is removed
+        let to_remove: Vec<(usize, usize)> = candidates
+            .drain(..)
+            .filter(|&(i, j)| {
+                queued[i][j] = false;
+                grid[i][j] && rule.removable(counts[i][j])
+            })
+            .collect();
 
         // Nothing can be removed at this point
         if to_remove.is_empty() {
@@ -135,13 +428,460 @@ fn find_rolls_mod(input: &[&str]) -> i32 {
         }
 
         // Apply the removal vec to the grid (mutable)
-        for (i, j) in to_remove {
-            if grid[i][j] == '@' {
-                grid[i][j] = '.';
-                sum += 1;
+        for &(i, j) in &to_remove {
+            grid[i][j] = false;
+        }
+
+        // Update the neighbours, anything whose count now passes the rule goes next round
+        for &(i, j) in &to_remove {
+            for (ai, aj) in neighbours(&grid, rule.edge, &watchers, i, j) {
+                if !grid[ai][aj] {
+                    continue;
+                }
+                counts[ai][aj] -= 1;
+
+                if !queued[ai][aj] && rule.removable(counts[ai][aj]) {
+                    queued[ai][aj] = true;
+                    candidates.push((ai, aj));
+                }
             }
         }
+
+        rounds.push(to_remove);
     }
 
-    sum
+    RemovalHistory {
+        initial,
+        initial_rolls,
+        rounds,
+    }
+}
+
+// Which cells hold a roll under the rule
+fn occupancy(input: &[&str], rule: &Rule) -> Vec<Vec<bool>> {
+    let mut grid: Vec<Vec<bool>> = input
+        .iter()
+        .map(|row| row.chars().map(|ch| rule.is_occupied(ch)).collect())
+        .collect();
+    pad_for_wrap(&mut grid, rule.edge);
+    grid
+}
+
+// Wrapping needs every row the same width, otherwise stepping by an offset and back by its reverse
+// can land on a different cell, so short rows are padded with empty cells to the widest row
+// The other policies keep the rows as they are so a wall still starts where a short row ends
+fn pad_for_wrap(grid: &mut [Vec<bool>], edge: EdgePolicy) {
+    if edge != EdgePolicy::Wrap {
+        return;
+    }
+    let width = grid.iter().map(|row| row.len()).max().unwrap_or(0);
+    for row in grid.iter_mut() {
+        row.resize(width, false);
+    }
+}
+
+// Occupied neighbours of (i, j), cells outside the grid only count when the edge is a wall
+fn count_neighbours(
+    grid: &[Vec<bool>],
+    rule: &Rule,
+    offsets: &[(isize, isize)],
+    i: usize,
+    j: usize,
+) -> usize {
+    offsets
+        .iter()
+        .filter(|&&offset| match neighbour(grid, rule.edge, i, j, offset) {
+            Some((ai, aj)) => grid[ai][aj],
+            None => rule.edge == EdgePolicy::Wall,
+        })
+        .count()
+}
+
+// Cells at the given offsets from (i, j) that are inside the grid
+fn neighbours<'a>(
+    grid: &'a [Vec<bool>],
+    edge: EdgePolicy,
+    offsets: &'a [(isize, isize)],
+    i: usize,
+    j: usize,
+) -> impl Iterator<Item = (usize, usize)> + 'a {
+    offsets
+        .iter()
+        .filter_map(move |&offset| neighbour(grid, edge, i, j, offset))
+}
+
+// The cell at an offset from (i, j), None if it is out of bounds
+// When wrapping, rows and columns are taken modulo the grid size (see `pad_for_wrap`)
+fn neighbour(
+    grid: &[Vec<bool>],
+    edge: EdgePolicy,
+    i: usize,
+    j: usize,
+    (di, dj): (isize, isize),
+) -> Option<(usize, usize)> {
+    if edge == EdgePolicy::Wrap {
+        let ai = (i as isize + di).rem_euclid(grid.len() as isize) as usize;
+        let width = grid[ai].len() as isize;
+        if width == 0 {
+            return None;
+        }
+        return Some((ai, (j as isize + dj).rem_euclid(width) as usize));
+    }
+
+    let ai = i.checked_add_signed(di)?;
+    let aj = j.checked_add_signed(dj)?;
+
+    if ai >= grid.len() || aj >= grid[ai].len() {
+        return None;
+    }
+    Some((ai, aj))
+}
+
+// Bit-packed grid, one bit per cell with cell (r, c) at bit c % 64 of word c / 64 in row r
+// Bits past the width are always 0
+struct BitGrid {
+    width: usize,
+    height: usize,
+    stride: usize, // Words per row
+    cells: Vec<u64>,
+}
+
+impl BitGrid {
+    // None for empty or ragged grids
+    fn new(grid: &[Vec<bool>]) -> Option<BitGrid> {
+        let width = grid.first()?.len();
+        if width == 0 || grid.iter().any(|row| row.len() != width) {
+            return None;
+        }
+
+        let stride = width.div_ceil(64);
+        let mut cells = vec![0u64; grid.len() * stride];
+        for (r, row) in grid.iter().enumerate() {
+            for (c, &occupied) in row.iter().enumerate() {
+                if occupied {
+                    cells[r * stride + c / 64] |= 1 << (c % 64);
+                }
+            }
+        }
+
+        Some(BitGrid {
+            width,
+            height: grid.len(),
+            stride,
+            cells,
+        })
+    }
+
+    fn row(&self, r: usize) -> &[u64] {
+        &self.cells[r * self.stride..(r + 1) * self.stride]
+    }
+
+    fn bit(&self, row: &[u64], c: usize) -> u64 {
+        (row[c / 64] >> (c % 64)) & 1
+    }
+
+    // Word w of the row shifted so each cell sees its west neighbour, `fill` is what's west of column 0
+    fn west(&self, row: &[u64], w: usize, fill: u64) -> u64 {
+        let carry = if w == 0 { fill } else { row[w - 1] >> 63 };
+        (row[w] << 1) | carry
+    }
+
+    // Word w of the row shifted so each cell sees its east neighbour, `fill` is what's east of the
+    // last column
+    fn east(&self, row: &[u64], w: usize, fill: u64) -> u64 {
+        let carry = if w + 1 < self.stride {
+            row[w + 1] << 63
+        } else {
+            0
+        };
+        let mut word = (row[w] >> 1) | carry;
+        if w + 1 == self.stride {
+            word |= fill << ((self.width - 1) % 64);
+        }
+        word
+    }
+}
+
+// Removal rounds on the bit-packed grid, returns how many rolls went in each round
+// Every word of a row is handled at once: the neighbour planes (above, below and the west/east
+// shifts of each) are added with a bit-sliced 4-bit counter, so bit c of counter word k is bit k of
+// cell c's neighbour count. The rule's comparison is then turned into a mask of the counts that pass.
+// None if the rule or grid can't be packed, the scalar engine handles those
+// Complexity: O(rounds * n / 64) where n is the number of cells
+fn bit_removal(input: &[&str], rule: &Rule) -> Option<Vec<usize>> {
+    let diagonals = match rule.neighbourhood {
+        Neighbourhood::Moore | Neighbourhood::MooreRadius(1) => true,
+        Neighbourhood::VonNeumann => false,
+        _ => return None,
+    };
+
+    let mut grid = BitGrid::new(&occupancy(input, rule))?;
+    let (h, stride) = (grid.height, grid.stride);
+
+    // Counts 0..=8 that pass the rule
+    let passing: Vec<u64> = (0..=8u64).filter(|&v| rule.removable(v as usize)).collect();
+
+    // Rows standing in for the outside of the grid
+    let outside = match rule.edge {
+        EdgePolicy::Wall => vec![!0u64; stride],
+        _ => vec![0u64; stride],
+    };
+    let edge_fill = |grid: &BitGrid, row: &[u64], west: bool| -> u64 {
+        match rule.edge {
+            EdgePolicy::Empty => 0,
+            EdgePolicy::Wall => 1,
+            EdgePolicy::Wrap if west => grid.bit(row, grid.width - 1),
+            EdgePolicy::Wrap => grid.bit(row, 0),
+        }
+    };
+
+    let mut rounds = Vec::new();
+    let mut removable = vec![0u64; grid.cells.len()];
+
+    loop {
+        for r in 0..h {
+            let above = match (r, rule.edge) {
+                (0, EdgePolicy::Wrap) => grid.row(h - 1),
+                (0, _) => &outside[..],
+                _ => grid.row(r - 1),
+            };
+            let below = match (r + 1 == h, rule.edge) {
+                (true, EdgePolicy::Wrap) => grid.row(0),
+                (true, _) => &outside[..],
+                _ => grid.row(r + 1),
+            };
+            let row = grid.row(r);
+
+            // Fill bits for the west/east shifts of each of the three rows
+            let fills = [above, row, below]
+                .map(|x| (edge_fill(&grid, x, true), edge_fill(&grid, x, false)));
+
+            for w in 0..stride {
+                let planes = [
+                    above[w],
+                    below[w],
+                    grid.west(row, w, fills[1].0),
+                    grid.east(row, w, fills[1].1),
+                    grid.west(above, w, fills[0].0),
+                    grid.east(above, w, fills[0].1),
+                    grid.west(below, w, fills[2].0),
+                    grid.east(below, w, fills[2].1),
+                ];
+                let used = if diagonals { 8 } else { 4 };
+
+                // Bit-sliced ripple add of each plane into a 4-bit counter
+                let mut counter = [0u64; 4];
+                for &plane in &planes[..used] {
+                    let mut carry = plane;
+                    for bit in counter.iter_mut() {
+                        let next = *bit & carry;
+                        *bit ^= carry;
+                        carry = next;
+                    }
+                }
+
+                // Cells whose count equals one of the passing values
+                let mut pass = 0u64;
+                for &v in &passing {
+                    let mut eq = !0u64;
+                    for (k, bit) in counter.iter().enumerate() {
+                        eq &= if (v >> k) & 1 == 1 { *bit } else { !*bit };
+                    }
+                    pass |= eq;
+                }
+
+                removable[r * stride + w] = row[w] & pass;
+            }
+        }
+
+        let count: usize = removable.iter().map(|w| w.count_ones() as usize).sum();
+        if count == 0 {
+            break;
+        }
+
+        // Apply the whole round at once
+        for (cell, gone) in grid.cells.iter_mut().zip(&removable) {
+            *cell &= !gone;
+        }
+        rounds.push(count);
+    }
+
+    Some(rounds)
+}
+
+// Builds the rule from the command line, anything not given keeps the puzzle default
+fn parse_rule(args: &[String]) -> Rule {
+    let mut rule = Rule::default();
+
+    for arg in args {
+        if let Some(n) = arg.strip_prefix("--neighbourhood=") {
+            rule.neighbourhood = match n {
+                "moore" => Neighbourhood::Moore,
+                "von-neumann" => Neighbourhood::VonNeumann,
+                _ if n.starts_with("moore:") => {
+                    Neighbourhood::MooreRadius(n[6..].parse().expect("radius must be a number"))
+                }
+                _ if n.starts_with("custom:") => Neighbourhood::Custom(
+                    n[7..]
+                        .split(';')
+                        .map(|pair| {
+                            let (di, dj) = pair.split_once(',').expect("offsets are DI,DJ");
+                            (
+                                di.trim().parse().expect("offset must be a number"),
+                                dj.trim().parse().expect("offset must be a number"),
+                            )
+                        })
+                        .collect(),
+                ),
+                _ => panic!("unknown neighbourhood {}", n),
+            };
+        } else if let Some(chars) = arg.strip_prefix("--occupied=") {
+            rule.occupied = chars.chars().collect();
+        } else if let Some(r) = arg.strip_prefix("--rule=") {
+            // Longest operators first so "<=" isn't read as "<"
+            let (comparison, threshold) = [
+                ("<=", Comparison::LessEq),
+                (">=", Comparison::GreaterEq),
+                ("<", Comparison::Less),
+                (">", Comparison::Greater),
+                ("=", Comparison::Equal),
+            ]
+            .iter()
+            .find_map(|(op, cmp)| r.strip_prefix(op).map(|t| (*cmp, t)))
+            .expect("rule must start with <, <=, =, >= or >");
+            rule.comparison = comparison;
+            rule.threshold = threshold.parse().expect("threshold must be a number");
+        } else if let Some(edge) = arg.strip_prefix("--edge=") {
+            rule.edge = match edge {
+                "empty" => EdgePolicy::Empty,
+                "wall" => EdgePolicy::Wall,
+                "wrap" => EdgePolicy::Wrap,
+                _ => panic!("--edge must be one of empty, wall or wrap"),
+            };
+        }
+    }
+
+    rule
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const COMPARISONS: [Comparison; 5] = [
+        Comparison::Less,
+        Comparison::LessEq,
+        Comparison::Equal,
+        Comparison::GreaterEq,
+        Comparison::Greater,
+    ];
+    const EDGES: [EdgePolicy; 3] = [EdgePolicy::Empty, EdgePolicy::Wall, EdgePolicy::Wrap];
+
+    // Rows are `width` wide, or anywhere from 1 to 9 cells when `width` is None
+    fn grids(seed: u64, count: usize, width: Option<usize>) -> Vec<Vec<String>> {
+        let mut rng = aoc25::xorshift(seed);
+        let mut next = move || rng() as usize;
+
+        (0..count)
+            .map(|_| {
+                let h = 1 + next() % 9;
+                (0..h)
+                    .map(|_| {
+                        let w = width.unwrap_or_else(|| 1 + next() % 9);
+                        (0..w)
+                            .map(|_| if next() % 3 == 0 { '.' } else { '@' })
+                            .collect()
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    fn rules(neighbourhood: Neighbourhood) -> Vec<Rule> {
+        let mut rules = Vec::new();
+        for comparison in COMPARISONS {
+            for threshold in 0..=8 {
+                for edge in EDGES {
+                    rules.push(Rule {
+                        neighbourhood: neighbourhood.clone(),
+                        comparison,
+                        threshold,
+                        edge,
+                        ..Rule::default()
+                    });
+                }
+            }
+        }
+        rules
+    }
+
+    // Rescans the whole grid every round, what the worklist is meant to be equivalent to
+    fn naive_rounds(input: &[&str], rule: &Rule) -> Vec<usize> {
+        let mut grid = occupancy(input, rule);
+        let offsets = rule.neighbourhood.offsets();
+        let mut rounds = Vec::new();
+
+        loop {
+            let mut to_remove = Vec::new();
+            for i in 0..grid.len() {
+                for j in 0..grid[i].len() {
+                    if grid[i][j] && rule.removable(count_neighbours(&grid, rule, &offsets, i, j)) {
+                        to_remove.push((i, j));
+                    }
+                }
+            }
+            if to_remove.is_empty() {
+                return rounds;
+            }
+            for &(i, j) in &to_remove {
+                grid[i][j] = false;
+            }
+            rounds.push(to_remove.len());
+        }
+    }
+
+    #[test]
+    fn worklist_matches_rescan_on_uneven_rows() {
+        let rules: Vec<Rule> = [Neighbourhood::Moore, Neighbourhood::VonNeumann]
+            .into_iter()
+            .flat_map(rules)
+            .collect();
+
+        for grid in grids(0x9e3779b97f4a7c15, 60, None) {
+            let input: Vec<&str> = grid.iter().map(|row| row.as_str()).collect();
+            for rule in &rules {
+                assert_eq!(
+                    simulate_removal(&input, rule).round_counts(),
+                    naive_rounds(&input, rule),
+                    "{:?} {:?}",
+                    grid,
+                    rule
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn bit_engine_matches_scalar() {
+        let rules: Vec<Rule> = [Neighbourhood::Moore, Neighbourhood::VonNeumann]
+            .into_iter()
+            .flat_map(rules)
+            .collect();
+
+        // Widths either side of the 64-bit word boundary, so the shifts carry between words
+        for (seed, width) in [(1, 1), (2, 5), (3, 63), (4, 64), (5, 65), (6, 130)] {
+            for grid in grids(0x2545f4914f6cdd1du64.wrapping_mul(seed), 8, Some(width)) {
+                let input: Vec<&str> = grid.iter().map(|row| row.as_str()).collect();
+                for rule in &rules {
+                    assert_eq!(
+                        bit_removal(&input, rule),
+                        Some(simulate_removal(&input, rule).round_counts()),
+                        "{:?} {:?}",
+                        grid,
+                        rule
+                    );
+                }
+            }
+        }
+    }
 }
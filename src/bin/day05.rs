@@ -1,30 +1,114 @@
-use aoc25::read_lines;
+use aoc25::{merge_ranges, read_lines};
+use std::fmt;
 
 // Day 5
 // Output: Solution -> Part1: 613 || Part2: 336495597913098
+// Range lines can be any of:
+//   a-b, a - b, -10--3   inclusive, bounds may be negative
+//   42, -7               a single ID
+//   a-                   a and everything above it
+//   a..b, a..=b, a.., ..b, ..=b   Rust style, .. excludes the end and ..= includes it
+//   [a,b] [a,b) (a,b] (a,b)       interval notation, a side left empty is unbounded
+// Flags: --clamp=LO,HI  only count IDs in LO..=HI for part 2, otherwise unbounded ranges give inf
+//        --explain[=ID]  why each ingredient (or just ID) is fresh or spoiled
+//        --diff OLD NEW  compare two range databases (files, ranges above the first blank line)
+//        --normalise [FILE]  print the merged, sorted ranges of FILE (default the puzzle input)
 fn main() {
     let input = include_str!("../../inputs/day05.txt");
 
     let arr: Vec<&str> = read_lines(input).collect();
 
-    let (p1, p2) = solve(&arr);
+    let args: Vec<String> = std::env::args().collect();
+    if let Some(i) = args.iter().position(|a| a == "--diff") {
+        let (old, new) = match (args.get(i + 1), args.get(i + 2)) {
+            (Some(old), Some(new)) => (load_database(old), load_database(new)),
+            _ => panic!("--diff needs the OLD and NEW files"),
+        };
+        print_diff(&old, &new);
+        return;
+    }
+    if let Some(i) = args.iter().position(|a| a == "--normalise") {
+        let set = match args.get(i + 1).filter(|a| !a.starts_with("--")) {
+            Some(path) => load_database(path),
+            None => {
+                let (lines, _) = get_ranges(&arr[..get_split_point(&arr)]);
+                RangeSet::new(&lines.iter().map(|&(_, r)| r).collect::<Vec<_>>())
+            }
+        };
+        for range in set.ranges() {
+            println!("{}", range);
+        }
+        return;
+    }
+
+    let clamp = args
+        .iter()
+        .find_map(|a| a.strip_prefix("--clamp="))
+        .map(|c| {
+            let (lo, hi) = c.split_once(',').expect("--clamp is LO,HI");
+            (
+                lo.trim().parse().expect("--clamp bound must be a number"),
+                hi.trim().parse().expect("--clamp bound must be a number"),
+            )
+        });
+
+    let explain = args.iter().find_map(|a| {
+        a.strip_prefix("--explain")
+            .map(|id| match id.strip_prefix('=') {
+                Some(id) => vec![id.parse::<i64>().expect("--explain ID must be a number")],
+                None => Vec::new(),
+            })
+    });
+    if let Some(ids) = explain {
+        let split = get_split_point(&arr);
+        let (ranges, _) = get_ranges(&arr[..split]);
+        let ids = if ids.is_empty() {
+            arr[split + 1..]
+                .iter()
+                .map(|l| l.parse().unwrap())
+                .collect()
+        } else {
+            ids
+        };
+        for id in ids {
+            println!("{}", explain_id(&ranges, id));
+        }
+    }
+
+    let ((p1, p2), errors) = solve(&arr, clamp);
+    for err in &errors {
+        eprintln!("{}", err);
+    }
     println!("Solution -> Part1: {} || Part2: {}", p1, p2);
 }
 
-fn solve(input: &[&str]) -> (i32, i64) {
-    (find_fresh_part1(&input), find_ranges_part2(&input))
+// Lines that fail to parse are left out and returned as errors
+fn solve(input: &[&str], clamp: Option<(i64, i64)>) -> ((i32, Total), Vec<RangeError>) {
+    // Ranges are the lines above the blank, ingredients the lines below it
+    let split = get_split_point(input);
+    let (lines, errors) = get_ranges(&input[..split]);
+    let ranges: Vec<IdRange> = lines.iter().map(|&(_, r)| r).collect();
+    let ingredients = &input[split + 1..];
+
+    (
+        (
+            find_fresh_part1(&ranges, ingredients),
+            find_ranges_part2(&ranges, clamp),
+        ),
+        errors,
+    )
 }
 
 // Part 1
-// Easy solution, extract the ranges (s, e) from all the lines above blank
-// Loop through each range and check if the ingredients are fresh and sum
-// Complexity: O(n*m), n = ranges, m = ingredients
-fn find_fresh_part1(input: &[&str]) -> i32 {
-    let ranges = get_ranges(&input);
+// Merge the ranges (s, e) once into sorted, disjoint ranges
+// Then each ingredient only needs a binary search to find the one range that could hold it
+// Complexity: O(n log n + m log n), n = ranges, m = ingredients
+fn find_fresh_part1(ranges: &[IdRange], ingredients: &[&str]) -> i32 {
+    let fresh = RangeSet::new(ranges);
     let mut sum = 0;
 
-    for i in get_split_point(&input) + 1..input.len() {
-        if in_range(&ranges, input[i].parse::<i64>().unwrap()) {
+    for line in ingredients {
+        if fresh.contains(line.parse::<i64>().unwrap()) {
             sum += 1;
         }
     }
@@ -32,59 +116,415 @@ fn find_fresh_part1(input: &[&str]) -> i32 {
     sum
 }
 
+// Total number of fresh IDs, an unbounded range covers infinitely many
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Total {
+    Finite(i128),
+    Infinite,
+}
+
+impl fmt::Display for Total {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Total::Finite(n) => write!(f, "{}", n),
+            Total::Infinite => write!(f, "inf"),
+        }
+    }
+}
+
 // Part 2 - Not so easy
 // Only get the ranges as we don't care about the ingredients
-// Sort the pairs (s, e) on both
-// Check overlap by comparing s, e of each range and sum if no overlap
-// Otherwise do an inclusive sum if there is an overlap
-// Complexity: `sort_unstable_by` is O(n log n) worstcase, then O(n) on the check
-fn find_ranges_part2(input: &[&str]) -> i64 {
-    let ranges = get_ranges(input);
+// Merge them into sorted, disjoint ranges (see `RangeSet`) and sum the lengths
+// With a clamp the set is cut down to LO..=HI first, which leaves only finite bounds, without one
+// any unbounded range makes the total infinite
+// Complexity: the merge sort is O(n log n) worstcase, then O(n) to sum
+fn find_ranges_part2(ranges: &[IdRange], clamp: Option<(i64, i64)>) -> Total {
+    let fresh = RangeSet::new(ranges);
+
+    match clamp {
+        Some((lo, hi)) => fresh
+            .intersection(&RangeSet::new(&[IdRange {
+                start: Some(lo),
+                end: Some(hi),
+            }]))
+            .covered(),
+        None => fresh.covered(),
+    }
+}
+
+// Sorted, disjoint, non-adjacent inclusive ranges, the merged form of a range database
+// Bounds are widened to i128 so unbounded sides can sit outside the i64 range and +-1 never overflows
+// Only a side written unbounded is stored as a sentinel, a bound written as i64::MIN/MAX stays
+// finite so its IDs can still be counted, every finite bound stays inside the i64 range
+#[derive(Debug, Clone)]
+struct RangeSet {
+    ranges: Vec<(i128, i128)>,
+}
+
+const UNBOUNDED_START: i128 = i128::MIN;
+const UNBOUNDED_END: i128 = i128::MAX;
+
+impl RangeSet {
+    // Merge overlapping and adjacent ranges
+    // Complexity: O(n log n)
+    fn new(ranges: &[IdRange]) -> RangeSet {
+        let wide: Vec<(i128, i128)> = ranges
+            .iter()
+            .map(|r| {
+                (
+                    r.start.map_or(UNBOUNDED_START, i128::from),
+                    r.end.map_or(UNBOUNDED_END, i128::from),
+                )
+            })
+            .collect();
+        RangeSet::clipped(merge_ranges(&wide))
+    }
 
-    if ranges.is_empty() {
-        return 0;
+    // Drop the pieces of sorted, disjoint ranges that hold no i64 IDs, e.g. what's left of `5-`
+    // after taking away `5-9223372036854775807`
+    // The +-1 in the set operations only ever steps past an i64 limit in such a piece
+    fn clipped(ranges: Vec<(i128, i128)>) -> RangeSet {
+        let (min, max) = (i64::MIN as i128, i64::MAX as i128);
+        let ranges = ranges
+            .into_iter()
+            .filter(|&(s, e)| s <= max && e >= min && s <= e)
+            .collect();
+        RangeSet { ranges }
     }
 
-    // Reassign ranges so we can mutate (sort)
-    let mut ranges = ranges.to_vec();
+    // The IDs covered, with unbounded sides at the i64 limits
+    fn ids(&self) -> Vec<(i128, i128)> {
+        let (min, max) = (i64::MIN as i128, i64::MAX as i128);
+        self.ranges
+            .iter()
+            .map(|&(s, e)| (s.max(min), e.min(max)))
+            .collect()
+    }
 
-    // Sort by start, then by end
-    ranges.sort_unstable_by(|a, b| a.0.cmp(&b.0).then(a.1.cmp(&b.1)));
+    // Back to ID ranges, in order
+    fn ranges(&self) -> Vec<IdRange> {
+        // Only the unbounded markers are outside i64 (see `clipped`)
+        let narrow = |v: i128| match v {
+            UNBOUNDED_START | UNBOUNDED_END => None,
+            v => Some(i64::try_from(v).expect("finite bounds are clipped to i64")),
+        };
+        self.ranges
+            .iter()
+            .map(|&(s, e)| IdRange {
+                start: narrow(s),
+                end: narrow(e),
+            })
+            .collect()
+    }
 
-    let (mut cur_start, mut cur_end) = ranges[0];
-    let mut total: i64 = 0;
+    // Binary search for the last range starting at or before the value
+    // Complexity: O(log n)
+    fn contains(&self, id: i64) -> bool {
+        let id = id as i128;
+        let idx = self.ranges.partition_point(|&(start, _)| start <= id);
+        idx > 0 && self.ranges[idx - 1].1 >= id
+    }
 
-    for &(s, e) in &ranges[1..] {
-        if s <= cur_end {
-            // Overlapping – extend current interval if needed
-            if e > cur_end {
-                cur_end = e;
+    // Number of IDs covered
+    fn covered(&self) -> Total {
+        let mut total: i128 = 0;
+        for &(s, e) in &self.ranges {
+            if s == UNBOUNDED_START || e == UNBOUNDED_END {
+                return Total::Infinite;
             }
-        } else {
-            // No overlap – close off previous interval
-            total += cur_end - cur_start + 1; // inclusive length
-            cur_start = s;
-            cur_end = e;
+            total += e - s + 1; // inclusive length
         }
+        Total::Finite(total)
+    }
+
+    fn union(&self, other: &RangeSet) -> RangeSet {
+        let all: Vec<(i128, i128)> = self.ranges.iter().chain(&other.ranges).copied().collect();
+        RangeSet::clipped(merge_ranges(&all))
     }
 
-    // Final interval
-    total += cur_end - cur_start + 1;
+    // Two pointer walk, keep the overlap of each pair and step past whichever ends first
+    // Complexity: O(n + m)
+    fn intersection(&self, other: &RangeSet) -> RangeSet {
+        let (a, b) = (&self.ranges, &other.ranges);
+        let (mut i, mut j) = (0, 0);
+        let mut out = Vec::new();
+
+        while i < a.len() && j < b.len() {
+            let s = a[i].0.max(b[j].0);
+            let e = a[i].1.min(b[j].1);
+            if s <= e {
+                out.push((s, e));
+            }
+            if a[i].1 < b[j].1 {
+                i += 1;
+            } else {
+                j += 1;
+            }
+        }
+        RangeSet::clipped(out)
+    }
+
+    // IDs in self but not in other
+    // Each of our ranges is cut by the other ranges overlapping it, which are in order
+    // Complexity: O(n + m)
+    fn difference(&self, other: &RangeSet) -> RangeSet {
+        let b = &other.ranges;
+        let mut j = 0;
+        let mut out = Vec::new();
+
+        for &(s, e) in &self.ranges {
+            // Skip ranges that end before this one starts
+            while j < b.len() && b[j].1 < s {
+                j += 1;
+            }
+
+            // `cur` is the first ID not yet cut or kept, None once nothing is left
+            let mut cur = Some(s);
+            let mut k = j;
+            while let Some(c) = cur
+                && k < b.len()
+                && b[k].0 <= e
+            {
+                if b[k].0 > c {
+                    out.push((c, b[k].0 - 1));
+                }
+                // An unbounded end cuts everything after it
+                cur = b[k].1.checked_add(1).map(|next| next.max(c));
+                k += 1;
+            }
+            if let Some(c) = cur
+                && c <= e
+            {
+                out.push((c, e));
+            }
+        }
+        RangeSet::clipped(out)
+    }
+}
+
+// Equal when they hold the same IDs, so `5-` and `5-9223372036854775807` compare equal even
+// though only the first is infinite
+impl PartialEq for RangeSet {
+    fn eq(&self, other: &RangeSet) -> bool {
+        self.ids() == other.ids()
+    }
+}
+
+impl Eq for RangeSet {}
+
+// An inclusive range of IDs, None means unbounded on that side
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct IdRange {
+    start: Option<i64>,
+    end: Option<i64>,
+}
+
+// Written back in the a-b style
+impl fmt::Display for IdRange {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match (self.start, self.end) {
+            (Some(s), Some(e)) if s == e => write!(f, "{}", s),
+            (Some(s), Some(e)) => write!(f, "{}-{}", s, e),
+            (Some(s), None) => write!(f, "{}-", s),
+            (None, Some(e)) => write!(f, "..={}", e),
+            (None, None) => write!(f, ".."),
+        }
+    }
+}
+
+impl IdRange {
+    fn contains(&self, id: i64) -> bool {
+        let (s, e) = self.bounds();
+        s <= id && id <= e
+    }
+
+    // Inclusive (s, e) with the unbounded sides at the i64 limits, fine for lookups since no
+    // ingredient ID can be outside them
+    fn bounds(&self) -> (i64, i64) {
+        (self.start.unwrap_or(i64::MIN), self.end.unwrap_or(i64::MAX))
+    }
+}
+
+// A range line that couldn't be read, `line` is 1-based
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct RangeError {
+    line: usize,
+    text: String,
+    kind: RangeErrorKind,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum RangeErrorKind {
+    // Not a number, or too big for an i64
+    BadNumber(String),
+    // Doesn't match any of the range forms
+    Syntax,
+    // Holds no IDs, e.g. 5-3 or [3,3)
+    Empty,
+}
 
-    total
+impl fmt::Display for RangeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {}: {:?}: ", self.line, self.text)?;
+        match &self.kind {
+            RangeErrorKind::BadNumber(n) => write!(f, "{:?} is not a valid ID", n),
+            RangeErrorKind::Syntax => write!(f, "not a range"),
+            RangeErrorKind::Empty => write!(f, "range contains no IDs"),
+        }
+    }
 }
 
-// Takes the list of the first part of the input and returns a list of (s, e)
-fn get_ranges(input: &[&str]) -> Vec<(i64, i64)> {
+// Takes the list of the first part of the input and returns the ranges with their 1-based line
+// numbers, and any lines that failed
+fn get_ranges(input: &[&str]) -> (Vec<(usize, IdRange)>, Vec<RangeError>) {
     let mut ranges = vec![];
-    for i in 0..get_split_point(&input) {
-        let pair = input[i].split('-').collect::<Vec<&str>>();
-        ranges.push((
-            pair[0].parse::<i64>().unwrap(),
-            pair[1].parse::<i64>().unwrap(),
-        ));
+    let mut errors = vec![];
+    for (i, line) in input.iter().enumerate() {
+        match parse_range(line) {
+            Ok(range) => ranges.push((i + 1, range)),
+            Err(kind) => errors.push(RangeError {
+                line: i + 1,
+                text: line.to_string(),
+                kind,
+            }),
+        }
+    }
+    (ranges, errors)
+}
+
+// Parses one range line, see the top of the file for the forms
+fn parse_range(line: &str) -> Result<IdRange, RangeErrorKind> {
+    let text = line.trim();
+
+    let (start, end) = if text.starts_with(['[', '(']) {
+        parse_interval(text)?
+    } else if let Some((lo, hi)) = text.split_once("..") {
+        let start = parse_bound(lo)?;
+        let end = match hi.strip_prefix('=') {
+            Some(hi) if hi.trim().is_empty() => return Err(RangeErrorKind::Syntax),
+            Some(hi) => parse_bound(hi)?,
+            // Exclusive end, `..i64::MIN` can't hold anything
+            None => match parse_bound(hi)? {
+                Some(e) => Some(e.checked_sub(1).ok_or(RangeErrorKind::Empty)?),
+                None => None,
+            },
+        };
+        (start, end)
+    } else {
+        // a-b with an optional sign on each side, so split after the first number
+        let (start, rest) = take_number(text)?;
+        let rest = rest.trim_start();
+        if rest.is_empty() {
+            (Some(start), Some(start))
+        } else {
+            let rest = rest.strip_prefix('-').ok_or(RangeErrorKind::Syntax)?;
+            (Some(start), parse_bound(rest)?)
+        }
+    };
+
+    if let (Some(s), Some(e)) = (start, end)
+        && s > e
+    {
+        return Err(RangeErrorKind::Empty);
+    }
+    Ok(IdRange { start, end })
+}
+
+// [a,b] [a,b) (a,b] (a,b), open sides are moved in by one to make the range inclusive
+fn parse_interval(text: &str) -> Result<(Option<i64>, Option<i64>), RangeErrorKind> {
+    let open_start = text.starts_with('(');
+    let open_end = match text.chars().last() {
+        Some(']') => false,
+        Some(')') => true,
+        _ => return Err(RangeErrorKind::Syntax),
+    };
+    if text.len() < 2 {
+        return Err(RangeErrorKind::Syntax);
+    }
+
+    let (lo, hi) = text[1..text.len() - 1]
+        .split_once(',')
+        .ok_or(RangeErrorKind::Syntax)?;
+
+    let mut start = parse_bound(lo)?;
+    let mut end = parse_bound(hi)?;
+    if open_start && let Some(s) = start {
+        start = Some(s.checked_add(1).ok_or(RangeErrorKind::Empty)?);
+    }
+    if open_end && let Some(e) = end {
+        end = Some(e.checked_sub(1).ok_or(RangeErrorKind::Empty)?);
     }
-    ranges
+    Ok((start, end))
+}
+
+// A whole (trimmed) string as a bound, empty means unbounded
+fn parse_bound(text: &str) -> Result<Option<i64>, RangeErrorKind> {
+    let text = text.trim();
+    if text.is_empty() {
+        return Ok(None);
+    }
+    text.parse()
+        .map(Some)
+        .map_err(|_| RangeErrorKind::BadNumber(text.to_string()))
+}
+
+// Reads an optionally negative number from the front of the string, returns it and the rest
+fn take_number(text: &str) -> Result<(i64, &str), RangeErrorKind> {
+    let sign = usize::from(text.starts_with('-'));
+    let len = text[sign..]
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(text.len() - sign);
+    if len == 0 {
+        return Err(RangeErrorKind::Syntax);
+    }
+
+    let (number, rest) = text.split_at(sign + len);
+    let number = number
+        .parse()
+        .map_err(|_| RangeErrorKind::BadNumber(number.to_string()))?;
+    Ok((number, rest))
+}
+
+// Reads the ranges of a database file, up to the first blank line if there is one
+// Bad lines are reported and left out
+fn load_database(path: &str) -> RangeSet {
+    let text =
+        std::fs::read_to_string(path).unwrap_or_else(|e| panic!("can't read {}: {}", path, e));
+    let lines: Vec<&str> = read_lines(&text).collect();
+    let end = lines
+        .iter()
+        .position(|l| l.is_empty())
+        .unwrap_or(lines.len());
+
+    let (ranges, errors) = get_ranges(&lines[..end]);
+    for err in &errors {
+        eprintln!("{}: {}", path, err);
+    }
+    RangeSet::new(&ranges.iter().map(|&(_, r)| r).collect::<Vec<_>>())
+}
+
+// What changed between two versions of the database
+fn print_diff(old: &RangeSet, new: &RangeSet) {
+    let fresh = new.difference(old);
+    let spoiled = old.difference(new);
+
+    for (label, set) in [("Became fresh", &fresh), ("Became spoiled", &spoiled)] {
+        let ranges: Vec<String> = set.ranges().iter().map(|r| r.to_string()).collect();
+        println!("{}: {} IDs in {}", label, set.covered(), ranges.join(", "));
+    }
+
+    let delta = match (old.covered(), new.covered()) {
+        (Total::Finite(o), Total::Finite(n)) => format!("{:+}", n - o),
+        _ => String::from("n/a"),
+    };
+    println!(
+        "Covered: {} -> {} ({}), {} in both, {} in either",
+        old.covered(),
+        new.covered(),
+        delta,
+        old.intersection(new).covered(),
+        old.union(new).covered()
+    );
 }
 
 // Finds the split point and returns its index
@@ -92,12 +532,223 @@ fn get_split_point(input: &[&str]) -> usize {
     input.iter().position(|&x| x.is_empty()).unwrap()
 }
 
-// Checks whether or not an value is in any of the ranges in the first part
-fn in_range(ranges: &[(i64, i64)], value: i64) -> bool {
-    for &(start, end) in ranges {
-        if value as i64 >= start && value as i64 <= end {
-            return true;
+// Why an ingredient is fresh or spoiled, ranges are kept with their line numbers
+// `below`/`above` are the nearest ranges on each side with their distance, only set when spoiled
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Explanation {
+    id: i64,
+    covering: Vec<(usize, IdRange)>,
+    below: Option<(usize, IdRange, u64)>,
+    above: Option<(usize, IdRange, u64)>,
+}
+
+impl Explanation {
+    fn is_fresh(&self) -> bool {
+        !self.covering.is_empty()
+    }
+}
+
+impl fmt::Display for Explanation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.is_fresh() {
+            let lines: Vec<String> = self
+                .covering
+                .iter()
+                .map(|(line, r)| format!("line {} ({})", line, r))
+                .collect();
+            return write!(f, "{}: fresh, covered by {}", self.id, lines.join(", "));
+        }
+
+        write!(f, "{}: spoiled", self.id)?;
+        for (side, nearest) in [("below", self.below), ("above", self.above)] {
+            match nearest {
+                Some((line, r, d)) => {
+                    write!(f, ", nearest {} line {} ({}) {} away", side, line, r, d)?
+                }
+                None => write!(f, ", nothing {}", side)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+// Looks at every original range (not the merged ones) so overlapping lines all show up
+// Complexity: O(n), n = ranges
+fn explain_id(ranges: &[(usize, IdRange)], id: i64) -> Explanation {
+    let covering: Vec<(usize, IdRange)> = ranges
+        .iter()
+        .filter(|(_, r)| r.contains(id))
+        .copied()
+        .collect();
+
+    let (mut below, mut above) = (None, None);
+    if covering.is_empty() {
+        // A range that doesn't contain id is entirely on one side of it
+        below = ranges
+            .iter()
+            .filter(|(_, r)| r.bounds().1 < id)
+            .map(|&(line, r)| (line, r, id.abs_diff(r.bounds().1)))
+            .min_by_key(|&(_, _, d)| d);
+        above = ranges
+            .iter()
+            .filter(|(_, r)| r.bounds().0 > id)
+            .map(|&(line, r)| (line, r, r.bounds().0.abs_diff(id)))
+            .min_by_key(|&(_, _, d)| d);
+    }
+
+    Explanation {
+        id,
+        covering,
+        below,
+        above,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn set(lines: &[&str]) -> RangeSet {
+        let ranges: Vec<IdRange> = lines.iter().map(|l| parse_range(l).unwrap()).collect();
+        RangeSet::new(&ranges)
+    }
+
+    fn written(set: &RangeSet) -> Vec<String> {
+        set.ranges().iter().map(|r| r.to_string()).collect()
+    }
+
+    #[test]
+    fn set_operations() {
+        // a, b, a | b, a & b, a - b
+        let cases: [[&[&str]; 5]; 8] = [
+            // Open ends
+            [&["5-"], &["..=10"], &[".."], &["5-10"], &["11-"]],
+            [&[".."], &["0"], &[".."], &["0"], &["..=-1", "1-"]],
+            [&["3-"], &["1-2"], &["1-"], &[], &["3-"]],
+            // Adjacent and touching ranges
+            [&["1-3"], &["4-6"], &["1-6"], &[], &["1-3"]],
+            [
+                &["1-5", "8-9"],
+                &["5-8"],
+                &["1-9"],
+                &["5", "8"],
+                &["1-4", "9"],
+            ],
+            // i64 limits stay finite, only unbounded sides are infinite
+            [
+                &["5-"],
+                &["5-9223372036854775807"],
+                &["5-"],
+                &["5-9223372036854775807"],
+                &[],
+            ],
+            [
+                &["5-9223372036854775807"],
+                &["5-"],
+                &["5-"],
+                &["5-9223372036854775807"],
+                &[],
+            ],
+            [
+                &["..=-9223372036854775808"],
+                &["-9223372036854775808-0"],
+                &["..=0"],
+                &["-9223372036854775808"],
+                &[],
+            ],
+        ];
+
+        for [a, b, union, intersection, difference] in cases {
+            let (a_set, b_set) = (set(a), set(b));
+            assert_eq!(written(&a_set.union(&b_set)), union, "{:?} | {:?}", a, b);
+            assert_eq!(
+                written(&a_set.intersection(&b_set)),
+                intersection,
+                "{:?} & {:?}",
+                a,
+                b
+            );
+            assert_eq!(
+                written(&a_set.difference(&b_set)),
+                difference,
+                "{:?} - {:?}",
+                a,
+                b
+            );
+        }
+    }
+
+    #[test]
+    fn limits_are_finite() {
+        assert_eq!(set(&["5-"]), set(&["5-9223372036854775807"]));
+        assert_eq!(set(&["5-"]).covered(), Total::Infinite);
+        assert_eq!(
+            set(&["0-9223372036854775807"]).covered(),
+            Total::Finite(i64::MAX as i128 + 1)
+        );
+
+        // A clamp always leaves a finite count, even over every ID
+        let everything = [parse_range("..").unwrap()];
+        assert_eq!(
+            find_ranges_part2(&everything, Some((i64::MIN, i64::MAX))),
+            Total::Finite(1 << 64)
+        );
+        assert_eq!(
+            find_ranges_part2(&everything, Some((0, i64::MAX))),
+            Total::Finite(i64::MAX as i128 + 1)
+        );
+    }
+
+    // Small random sets, some with open ends, checked ID by ID against `contains`
+    #[test]
+    fn set_operations_match_contains() {
+        let mut next = aoc25::xorshift(0x9e3779b97f4a7c15);
+        let mut random_set = || {
+            let count = next() % 4;
+            let ranges: Vec<IdRange> = (0..count)
+                .map(|_| {
+                    let s = (next() % 17) as i64 - 8;
+                    let e = s + (next() % 5) as i64;
+                    IdRange {
+                        start: (!next().is_multiple_of(6)).then_some(s),
+                        end: (!next().is_multiple_of(6)).then_some(e),
+                    }
+                })
+                .collect();
+            RangeSet::new(&ranges)
+        };
+
+        for _ in 0..500 {
+            let (a, b) = (random_set(), random_set());
+            let (union, intersection, difference) =
+                (a.union(&b), a.intersection(&b), a.difference(&b));
+            for id in -12..=12 {
+                let (in_a, in_b) = (a.contains(id), b.contains(id));
+                assert_eq!(
+                    union.contains(id),
+                    in_a || in_b,
+                    "{:?} | {:?} at {}",
+                    a,
+                    b,
+                    id
+                );
+                assert_eq!(
+                    intersection.contains(id),
+                    in_a && in_b,
+                    "{:?} & {:?} at {}",
+                    a,
+                    b,
+                    id
+                );
+                assert_eq!(
+                    difference.contains(id),
+                    in_a && !in_b,
+                    "{:?} - {:?} at {}",
+                    a,
+                    b,
+                    id
+                );
+            }
         }
     }
-    false
 }
@@ -1,144 +1,601 @@
 use aoc25::read_lines_with_spaces;
+use std::fmt;
 
 // Day 6 - Took me a while to get part 2 right, I kept using read_lines which trimmed spaces
 // (like an idiot)
-// Output: Solution -> Part1: 4076006202939 || Part2: 7903168391557
+// Operators: + - * / % ^ min max, - / % ^ depend on the fold direction
+// Flags: --fold=left|right  left is ((a op b) op c), right is a op (b op c) (default left)
+//        --digits=forward|reverse  digits read left-to-right/top-to-bottom or the other way round
+//        --problems=ltr|rtl  order problems (and a column-major problem's numbers) are read in
+//        --operators=bottom|top  which line of the worksheet holds the operators
+//        The flags override both parts' reading orders, the defaults are the puzzle's
+//        --annotate  print every problem block with its numbers and value under both parts' readings
+//        --values  print each problem's exact value for both parts, in the order they're summed
+// Numbers, values and totals are exact up to i128
 fn main() {
     let input = include_str!("../../inputs/day06.txt");
 
     let arr: Vec<&str> = read_lines_with_spaces(input).collect();
-    let (p1, p2) = solve(&arr);
-    println!("Solution -> Part1: {} || Part2: {}", p1, p2);
+
+    let args: Vec<String> = std::env::args().collect();
+    let fold = match args.iter().find_map(|a| a.strip_prefix("--fold=")) {
+        None | Some("left") => Fold::Left,
+        Some("right") => Fold::Right,
+        Some(_) => panic!("--fold must be left or right"),
+    };
+    let orders = [ReadingOrder::PART1, ReadingOrder::PART2].map(|o| parse_order(&args, o));
+
+    if args.iter().any(|a| a == "--annotate")
+        && let Err(err) = annotate(&arr, fold, &orders)
+    {
+        eprintln!("{}", err);
+        std::process::exit(1);
+    }
+
+    match solve(&arr, fold, &orders) {
+        Ok((p1, p2)) => {
+            if args.iter().any(|a| a == "--values") {
+                for (part, (_, values)) in [(1, &p1), (2, &p2)] {
+                    for (problem, value) in values {
+                        println!(
+                            "Part{} cols {}-{} [{}]: {}",
+                            part, problem.start, problem.end, problem.op.op, value
+                        );
+                    }
+                }
+            }
+            println!("Solution -> Part1: {} || Part2: {}", p1.0, p2.0)
+        }
+        Err(err) => {
+            eprintln!("{}", err);
+            std::process::exit(1);
+        }
+    }
 }
 
-fn solve(input: &[&str]) -> (i64, i64) {
+// Each part's total, and every problem's value in the order they're summed
+type Solved = (i128, Vec<(Problem, i128)>);
+
+fn solve(
+    input: &[&str],
+    fold: Fold,
+    orders: &[ReadingOrder; 2],
+) -> Result<(Solved, Solved), WorksheetError> {
+    let grid = build_grid(input);
+
     // Part 1: original row-wise
-    let pg = get_problem_groups(input);
-    let op = get_operators(input);
-    let p1 = solve_worksheet(&pg, &op);
+    let (problems, pg) = read_worksheet(input, &grid, orders[0])?;
+    let p1 = solve_worksheet(&pg, &problems, fold)?;
 
     // Part 2: column-wise, right-to-left
-    let pg = get_problem_groups_part2(input);
-    // The effective problem for p2 of the worksheet is the same as p1
-    let p2 = solve_worksheet(&pg, &op);
+    // The effective problem for p2 of the worksheet is the same as p1, only the numbers change
+    let (problems, pg) = read_worksheet(input, &grid, orders[1])?;
+    let p2 = solve_worksheet(&pg, &problems, fold)?;
+
+    Ok((p1, p2))
+}
+
+// How numbers are laid out on a worksheet, parts 1 and 2 are the two presets
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct ReadingOrder {
+    major: Major,
+    // Digits of a number left-to-right (row-major) or top-to-bottom (column-major) when Forward
+    digits: Direction,
+    // Problems left-to-right when Forward, for column-major this is also the order of the numbers
+    // inside a problem
+    problems: Direction,
+    operators: OperatorRow,
+}
+
+impl ReadingOrder {
+    const PART1: ReadingOrder = ReadingOrder {
+        major: Major::Row,
+        digits: Direction::Forward,
+        problems: Direction::Forward,
+        operators: OperatorRow::Bottom,
+    };
+    const PART2: ReadingOrder = ReadingOrder {
+        major: Major::Column,
+        digits: Direction::Forward,
+        problems: Direction::Reverse,
+        operators: OperatorRow::Bottom,
+    };
+}
+
+// Whether each number runs along a row or down a column
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Major {
+    Row,
+    Column,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Direction {
+    Forward,
+    Reverse,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum OperatorRow {
+    Top,
+    Bottom,
+}
+
+// Apply --digits=, --problems= and --operators= on top of a preset
+fn parse_order(args: &[String], mut order: ReadingOrder) -> ReadingOrder {
+    for arg in args {
+        match arg.split_once('=') {
+            Some(("--digits", "forward")) => order.digits = Direction::Forward,
+            Some(("--digits", "reverse")) => order.digits = Direction::Reverse,
+            Some(("--digits", _)) => panic!("--digits must be forward or reverse"),
+            Some(("--problems", "ltr")) => order.problems = Direction::Forward,
+            Some(("--problems", "rtl")) => order.problems = Direction::Reverse,
+            Some(("--problems", _)) => panic!("--problems must be ltr or rtl"),
+            Some(("--operators", "top")) => order.operators = OperatorRow::Top,
+            Some(("--operators", "bottom")) => order.operators = OperatorRow::Bottom,
+            Some(("--operators", _)) => panic!("--operators must be top or bottom"),
+            _ => {}
+        }
+    }
+    order
+}
+
+// One line per problem block, left-to-right, with the part 1 and part 2 readings side by side
+// e.g. cols 0-2 [*] || Part1: 123 * 45 * 6 = 33210 || Part2: 356 * 24 * 1 = 8544
+// A problem that can't be evaluated shows its error instead of a value
+fn annotate(input: &[&str], fold: Fold, orders: &[ReadingOrder; 2]) -> Result<(), WorksheetError> {
+    let grid = build_grid(input);
+
+    let mut readings = Vec::new();
+    for &order in orders {
+        let (problems, groups) = read_worksheet(input, &grid, order)?;
+        let mut reading: Vec<(Problem, Vec<i128>)> = problems.into_iter().zip(groups).collect();
+        reading.sort_by_key(|(problem, _)| problem.start);
+        readings.push(reading);
+    }
+
+    // Both readings share the number rows so they find the same blocks
+    for (p1, p2) in readings[0].iter().zip(&readings[1]) {
+        let problem = p1.0;
+        println!(
+            "cols {}-{} [{}] || Part1: {} || Part2: {}",
+            problem.start,
+            problem.end,
+            problem.op.op,
+            annotate_problem(&p1.1, &p1.0.op, fold),
+            annotate_problem(&p2.1, &p2.0.op, fold)
+        );
+    }
+    Ok(())
+}
+
+// a op b op c = value, or the error evaluating it
+fn annotate_problem(nums: &[i128], op: &Operator, fold: Fold) -> String {
+    let shown: Vec<String> = nums.iter().map(|n| n.to_string()).collect();
+    let expr = shown.join(&format!(" {} ", op.op));
+    match evaluate(nums, op, fold) {
+        Ok(val) => format!("{} = {}", expr, val),
+        Err(err) => format!("{} -> {}", expr, err),
+    }
+}
+
+// Split off the operator row, bind the operators to the problem blocks and read every problem's
+// numbers in the given order
+fn read_worksheet(
+    input: &[&str],
+    grid: &[Vec<char>],
+    order: ReadingOrder,
+) -> Result<(Vec<Problem>, Vec<Vec<i128>>), WorksheetError> {
+    let last = input.len() - 1;
+    let (rows, line) = match order.operators {
+        OperatorRow::Top => (&grid[1..], input[0]),
+        OperatorRow::Bottom => (&grid[..last], input[last]),
+    };
+
+    let mut problems = get_problems(rows, &get_operators(line)?)?;
+    if order.problems == Direction::Reverse {
+        problems.reverse();
+    }
+
+    let groups = match order.major {
+        Major::Row => get_problem_groups(rows, &problems, order)?,
+        Major::Column => get_problem_groups_part2(rows, &problems, order)?,
+    };
+    Ok((problems, groups))
+}
+
+// Operators that can appear on the last line of the worksheet
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Op {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Rem,
+    Pow,
+    Min,
+    Max,
+}
+
+impl Op {
+    fn parse(token: &str) -> Option<Op> {
+        match token {
+            "+" => Some(Op::Add),
+            "-" => Some(Op::Sub),
+            "*" => Some(Op::Mul),
+            "/" => Some(Op::Div),
+            "%" => Some(Op::Rem),
+            "^" => Some(Op::Pow),
+            "min" => Some(Op::Min),
+            "max" => Some(Op::Max),
+            _ => None,
+        }
+    }
+
+    // a op b with checked arithmetic, the error is reported against the operator's column
+    // Division and remainder truncate toward zero like Rust's / and %
+    fn apply<T: Checked>(self, a: T, b: T, col: usize) -> Result<T, WorksheetError> {
+        let overflow = WorksheetError::Overflow { col };
+        match self {
+            Op::Add => a.checked_add(b).ok_or(overflow),
+            Op::Sub => a.checked_sub(b).ok_or(overflow),
+            Op::Mul => a.checked_mul(b).ok_or(overflow),
+            Op::Div | Op::Rem if b == T::ZERO => Err(WorksheetError::DivisionByZero { col }),
+            Op::Div => a.checked_div(b).ok_or(overflow),
+            Op::Rem => a.checked_rem(b).ok_or(overflow),
+            Op::Pow => {
+                let exp: u32 = b
+                    .try_into()
+                    .map_err(|_| WorksheetError::BadExponent { col })?;
+                a.checked_pow(exp).ok_or(overflow)
+            }
+            Op::Min => Ok(a.min(b)),
+            Op::Max => Ok(a.max(b)),
+        }
+    }
+}
+
+impl fmt::Display for Op {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let token = match self {
+            Op::Add => "+",
+            Op::Sub => "-",
+            Op::Mul => "*",
+            Op::Div => "/",
+            Op::Rem => "%",
+            Op::Pow => "^",
+            Op::Min => "min",
+            Op::Max => "max",
+        };
+        write!(f, "{}", token)
+    }
+}
+
+// The checked operations Op::apply needs, so a problem can be evaluated in i64 and redone in
+// i128 only when it overflows
+trait Checked: Copy + Ord + TryInto<u32> {
+    const ZERO: Self;
+    fn checked_add(self, rhs: Self) -> Option<Self>;
+    fn checked_sub(self, rhs: Self) -> Option<Self>;
+    fn checked_mul(self, rhs: Self) -> Option<Self>;
+    fn checked_div(self, rhs: Self) -> Option<Self>;
+    fn checked_rem(self, rhs: Self) -> Option<Self>;
+    fn checked_pow(self, exp: u32) -> Option<Self>;
+}
 
-    (p1, p2)
+macro_rules! impl_checked {
+    ($($t:ty),*) => {$(
+        impl Checked for $t {
+            const ZERO: Self = 0;
+            fn checked_add(self, rhs: Self) -> Option<Self> {
+                <$t>::checked_add(self, rhs)
+            }
+            fn checked_sub(self, rhs: Self) -> Option<Self> {
+                <$t>::checked_sub(self, rhs)
+            }
+            fn checked_mul(self, rhs: Self) -> Option<Self> {
+                <$t>::checked_mul(self, rhs)
+            }
+            fn checked_div(self, rhs: Self) -> Option<Self> {
+                <$t>::checked_div(self, rhs)
+            }
+            fn checked_rem(self, rhs: Self) -> Option<Self> {
+                <$t>::checked_rem(self, rhs)
+            }
+            fn checked_pow(self, exp: u32) -> Option<Self> {
+                <$t>::checked_pow(self, exp)
+            }
+        }
+    )*};
+}
+
+impl_checked!(i64, i128);
+
+// An operator and the columns it takes up on the worksheet (0-based, `col` is the first)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Operator {
+    op: Op,
+    col: usize,
+    len: usize,
+}
+
+// A block of columns start..=end holding one problem, and the operator found under it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Problem {
+    start: usize,
+    end: usize,
+    op: Operator,
+}
+
+// Which way a problem's numbers are combined
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Fold {
+    // ((a op b) op c)
+    Left,
+    // a op (b op c)
+    Right,
+}
+
+// Everything that can go wrong evaluating a worksheet, columns are 0-based
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum WorksheetError {
+    UnknownOperator { col: usize, token: String },
+    // A problem block with no operator under it, `col` is the block's first column
+    MissingOperator { col: usize },
+    // A second operator under the same problem block
+    ExtraOperator { col: usize },
+    // An operator that isn't under any problem block, or is under more than one
+    StrayOperator { col: usize },
+    EmptyGroup { col: usize },
+    DivisionByZero { col: usize },
+    // Result doesn't fit even in i128
+    Overflow { col: usize },
+    // A number on the worksheet with too many digits for i128, `col` is where it starts
+    NumberTooLarge { col: usize },
+    // Negative or too large exponent for ^
+    BadExponent { col: usize },
+}
+
+impl fmt::Display for WorksheetError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            WorksheetError::UnknownOperator { col, token } => {
+                write!(f, "column {}: unknown operator {:?}", col, token)
+            }
+            WorksheetError::MissingOperator { col } => {
+                write!(f, "column {}: problem has no operator", col)
+            }
+            WorksheetError::ExtraOperator { col } => {
+                write!(f, "column {}: problem already has an operator", col)
+            }
+            WorksheetError::StrayOperator { col } => {
+                write!(
+                    f,
+                    "column {}: operator isn't under exactly one problem",
+                    col
+                )
+            }
+            WorksheetError::EmptyGroup { col } => {
+                write!(f, "column {}: problem has no numbers", col)
+            }
+            WorksheetError::DivisionByZero { col } => write!(f, "column {}: division by zero", col),
+            WorksheetError::Overflow { col } => write!(f, "column {}: overflow", col),
+            WorksheetError::NumberTooLarge { col } => {
+                write!(f, "column {}: number too large", col)
+            }
+            WorksheetError::BadExponent { col } => write!(f, "column {}: invalid exponent", col),
+        }
+    }
 }
 
 // Part 1
-// Fairly easy solution, just get the problem groups (Vec<Vec<i64>>) and apply the operator to the
+// Fairly easy solution, just get the problem groups (Vec<Vec<i128>>) and apply the operator to the
 // group where the operator is
+// Totals are i128 so a worksheet of large products can't wrap, each problem's value is kept too
 // Complexity: O(n), n = size of grid
-fn solve_worksheet(problem: &Vec<Vec<i64>>, operator: &Vec<String>) -> i64 {
-    let mut total: i64 = 0;
-
-    for p in 0..problem.len() {
-        let row = problem.get(p).unwrap();
-
-        let val = match operator.get(p).map(|s| s.as_str()) {
-            Some("+") => row.iter().sum::<i64>(),
-            Some("-") => row.iter().copied().reduce(|acc, x| acc - x).unwrap(),
-            Some("*") => row.iter().product::<i64>(),
-            Some("/") => row.iter().copied().reduce(|acc, x| acc / x).unwrap(),
-            _ => 0,
-        };
+fn solve_worksheet(
+    groups: &[Vec<i128>],
+    problems: &[Problem],
+    fold: Fold,
+) -> Result<Solved, WorksheetError> {
+    let mut total: i128 = 0;
+    let mut values = Vec::with_capacity(problems.len());
 
-        total += val;
+    for (row, problem) in groups.iter().zip(problems) {
+        let op = &problem.op;
+        let val = evaluate(row, op, fold)?;
+        total = total
+            .checked_add(val)
+            .ok_or(WorksheetError::Overflow { col: op.col })?;
+        values.push((*problem, val));
     }
-    total
+    Ok((total, values))
 }
 
-// Find problem groups by reading the input lines left-to-right (p1 - ignoring spaces)
-// Group comumn-wise for each
-fn get_problem_groups(input: &[&str]) -> Vec<Vec<i64>> {
-    let mut groups: Vec<Vec<String>> = Vec::new();
-    let re = regex::Regex::new(r"\d+").unwrap();
-    for line in 0..input.len() {
-        groups.push(
-            re.find_iter(input[line])
-                .map(|g| g.as_str().to_string())
-                .collect(),
-        );
+// Fold the numbers of one problem with its operator
+// Done in i64 when every number fits, a problem that overflows it (or has a wider number) is
+// evaluated in i128
+fn evaluate(nums: &[i128], op: &Operator, fold: Fold) -> Result<i128, WorksheetError> {
+    let narrow: Option<Vec<i64>> = nums.iter().map(|&n| i64::try_from(n).ok()).collect();
+    match narrow.map(|narrow| evaluate_in(&narrow, op, fold)) {
+        Some(Ok(val)) => Ok(val as i128),
+        Some(Err(WorksheetError::Overflow { .. })) | None => evaluate_in(nums, op, fold),
+        Some(Err(err)) => Err(err),
+    }
+}
+
+fn evaluate_in<T: Checked>(nums: &[T], op: &Operator, fold: Fold) -> Result<T, WorksheetError> {
+    let (&first, rest) = match fold {
+        Fold::Left => nums.split_first(),
+        Fold::Right => nums.split_last(),
+    }
+    .ok_or(WorksheetError::EmptyGroup { col: op.col })?;
+
+    match fold {
+        Fold::Left => rest
+            .iter()
+            .try_fold(first, |acc, &x| op.op.apply(acc, x, op.col)),
+        Fold::Right => rest
+            .iter()
+            .rev()
+            .try_fold(first, |acc, &x| op.op.apply(x, acc, op.col)),
+    }
+}
+
+// Pad every line with spaces to the widest one so the worksheet can be indexed as a grid
+fn build_grid(input: &[&str]) -> Vec<Vec<char>> {
+    let width = input.iter().map(|l| l.chars().count()).max().unwrap_or(0);
+
+    input
+        .iter()
+        .map(|line| {
+            let mut row: Vec<char> = line.chars().collect();
+            row.resize(width, ' ');
+            row
+        })
+        .collect()
+}
+
+// Pair each problem block with the operator in line with it by column overlap
+// Blocks come from the number rows only, so an operator sitting in line with blank columns isn't
+// mistaken for a problem of its own
+// Complexity: O(r*c + p), r = rows, c = cols, p = operators
+fn get_problems(
+    rows: &[Vec<char>],
+    operators: &[Operator],
+) -> Result<Vec<Problem>, WorksheetError> {
+    let blocks = find_blocks(rows);
+    let mut bound: Vec<Option<Operator>> = vec![None; blocks.len()];
+
+    for op in operators {
+        let last = op.col + op.len - 1;
+        let mut overlapping = blocks
+            .iter()
+            .enumerate()
+            .filter(|&(_, &(start, end))| op.col <= end && start <= last)
+            .map(|(b, _)| b);
+
+        let (Some(b), None) = (overlapping.next(), overlapping.next()) else {
+            return Err(WorksheetError::StrayOperator { col: op.col });
+        };
+        if bound[b].is_some() {
+            return Err(WorksheetError::ExtraOperator { col: op.col });
+        }
+        bound[b] = Some(*op);
     }
 
-    let mut problem_groups: Vec<Vec<i64>> = vec![];
-    for i in 0..groups[0].len() {
-        let mut a: Vec<i64> = vec![];
-        for j in 0..groups.len() {
-            if let Some(val) = groups[j].get(i) {
-                a.push(val.parse::<i64>().unwrap());
+    blocks
+        .iter()
+        .zip(bound)
+        .map(|(&(start, end), op)| match op {
+            Some(op) => Ok(Problem { start, end, op }),
+            None => Err(WorksheetError::MissingOperator { col: start }),
+        })
+        .collect()
+}
+
+// Find problem groups by reading each row within the problem's columns (p1 - ignoring spaces)
+fn get_problem_groups(
+    rows: &[Vec<char>],
+    problems: &[Problem],
+    order: ReadingOrder,
+) -> Result<Vec<Vec<i128>>, WorksheetError> {
+    let re = regex::Regex::new(r"\d+").unwrap();
+
+    let mut problem_groups: Vec<Vec<i128>> = vec![];
+    for problem in problems {
+        let mut a: Vec<i128> = vec![];
+        for row in rows {
+            let text: String = row[problem.start..=problem.end].iter().collect();
+            for val in re.find_iter(&text) {
+                let digits: String = match order.digits {
+                    Direction::Forward => val.as_str().to_string(),
+                    Direction::Reverse => val.as_str().chars().rev().collect(),
+                };
+                let col = problem.start + text[..val.start()].chars().count();
+                a.push(parse_number(&digits, col)?);
             }
         }
         problem_groups.push(a);
     }
-    problem_groups
+    Ok(problem_groups)
+}
+
+// Digits are only ever 0-9 here so the only way parsing fails is a number past i128
+fn parse_number(digits: &str, col: usize) -> Result<i128, WorksheetError> {
+    digits
+        .parse()
+        .map_err(|_| WorksheetError::NumberTooLarge { col })
 }
 
 // Part 2
 // This was slightly more tricky because spaces must be presrved for the int order
-// Here we can use the space from one op to the next - 1 to work out the width of a group
+// The problem blocks give the width of each group
 // We can then treat this as a grid and keeping whitespaces get the column-wise groups as before
 // Complexity: O(r*c), r = rows, c = cols -> O(n)
-fn get_problem_groups_part2(input: &[&str]) -> Vec<Vec<i64>> {
-    // Build a rectangular grid of chars using the last line (operator row) as the width.
-    let op_line = input[input.len() - 1];
-    let width = op_line.chars().count();
-
-    let mut grid: Vec<Vec<char>> = Vec::with_capacity(input.len());
-    for line in input {
-        let mut row: Vec<char> = line.chars().collect();
-        if row.len() < width {
-            row.extend(std::iter::repeat(' ').take(width - row.len()));
-        } else if row.len() > width {
-            row.truncate(width);
-        }
-        grid.push(row);
-    }
+fn get_problem_groups_part2(
+    rows: &[Vec<char>],
+    problems: &[Problem],
+    order: ReadingOrder,
+) -> Result<Vec<Vec<i128>>, WorksheetError> {
+    let mut groups: Vec<Vec<i128>> = Vec::new();
 
-    // Problems are separated by a full column of spaces
-    let blocks = find_blocks(&grid);
-
-    let rows = grid.len();
-    let mut problems: Vec<Vec<i64>> = Vec::new();
+    for problem in problems {
+        let mut nums: Vec<i128> = Vec::new();
 
-    for (start, end) in blocks {
-        let mut nums: Vec<i64> = Vec::new();
-
-        // Each column within this block is one number (top digit at the top row)
-        for c in start..=end {
+        // Each column within this block is one number (most significant digit at the top row when
+        // reading forwards)
+        let cols: Vec<usize> = match order.problems {
+            Direction::Forward => (problem.start..=problem.end).collect(),
+            Direction::Reverse => (problem.start..=problem.end).rev().collect(),
+        };
+        for c in cols {
             let mut digits = String::new();
 
-            for r in 0..rows - 1 {
-                let ch = grid[r][c];
+            let column: Vec<char> = rows.iter().map(|row| row[c]).collect();
+            let column: Vec<char> = match order.digits {
+                Direction::Forward => column,
+                Direction::Reverse => column.into_iter().rev().collect(),
+            };
+            for ch in column {
                 if ch.is_ascii_digit() {
                     digits.push(ch);
                 }
             }
 
             if !digits.is_empty() {
-                let n: i64 = digits.parse().unwrap();
-                nums.push(n);
+                nums.push(parse_number(&digits, c)?);
             }
         }
 
-        problems.push(nums);
+        groups.push(nums);
     }
 
-    problems
+    Ok(groups)
 }
 
-// Assuming operators will always be the last line
-fn get_operators(input: &[&str]) -> Vec<String> {
-    let re = regex::Regex::new(r"[+\-*/]").unwrap();
-    let operators: Vec<String> = re
-        .find_iter(input[input.len() - 1])
-        .map(|g| g.as_str().to_string())
-        .collect();
-    operators
+// Operators are on the first or last line depending on the reading order
+// Every whitespace separated token on it must be an operator, its column is where it starts
+fn get_operators(line: &str) -> Result<Vec<Operator>, WorksheetError> {
+    let re = regex::Regex::new(r"\S+").unwrap();
+    re.find_iter(line)
+        .map(|g| {
+            // Columns count characters to match the grid, not bytes
+            let col = line[..g.start()].chars().count();
+            let len = g.as_str().chars().count();
+            match Op::parse(g.as_str()) {
+                Some(op) => Ok(Operator { op, col, len }),
+                None => Err(WorksheetError::UnknownOperator {
+                    col,
+                    token: g.as_str().to_string(),
+                }),
+            }
+        })
+        .collect()
 }
 
 // Scan the worksheet column-wise and splits into problem groups
-fn find_blocks(grid: &Vec<Vec<char>>) -> Vec<(usize, usize)> {
+fn find_blocks(grid: &[Vec<char>]) -> Vec<(usize, usize)> {
     let rows = grid.len();
     if rows == 0 {
         return Vec::new();
@@ -1,174 +1,502 @@
 use aoc25::read_lines;
 use std::collections::{HashMap, HashSet, VecDeque};
+use std::fmt;
 
 // Day 7 - I found this to be a difficult one!
 // Output: Solution -> Part1: 1667 || Part2: 62943905501815
+// Any number of S cells can appear anywhere in the grid, each one emits a beam downwards
+// Cells: . S  empty, beams pass straight through
+//        ^ v  split vertical beams into the cells left and right, horizontal beams pass through
+//        < >  split horizontal beams into the cells above and below, vertical beams pass through
+//        / \  mirrors, turn the beam 90 degrees
+//        #    absorber, every beam entering it ends
+//        U D L R  diodes, only beams travelling up/down/left/right get through, the rest end
+// A split beam starts in the cells either side and they apply their rule straight away, a splitter
+// there is hit side-on and passed through, so adjacent splitters don't feed each other
+// Part 2 is "inf" when a beam can loop back through a splitter, a beam stuck in a loop with no
+// splitter is one timeline that never ends
+// Flags: --weights=W1,W2,...  timelines emitted by each S in reading order (default 1 each), a source
+//        with weight 0 is switched off
 fn main() {
     let input = include_str!("../../inputs/day07.txt");
 
     let arr: Vec<&str> = read_lines(input).collect();
 
-    let (p1, p2) = solve(&arr);
-    println!("Solution -> Part1: {} || Part2: {}", p1, p2);
+    let weights: Option<Vec<i64>> = std::env::args()
+        .find_map(|a| a.strip_prefix("--weights=").map(String::from))
+        .map(|w| {
+            w.split(',')
+                .map(|n| n.trim().parse().expect("weights must be numbers"))
+                .collect()
+        });
+
+    match solve(&arr, weights.as_deref()) {
+        Ok((p1, p2)) => println!("Solution -> Part1: {} || Part2: {}", p1, p2),
+        Err(err) => {
+            eprintln!("{}", err);
+            std::process::exit(1);
+        }
+    }
+}
+
+fn solve(input: &[&str], weights: Option<&[i64]>) -> Result<(i64, Timelines), ManifoldError> {
+    let (grid, sources) = parse_grid(input, weights)?;
+    Ok((
+        read_tachyons_part1(&grid, &sources),
+        read_quantum_tachyons_part2(&grid, &sources)?,
+    ))
+}
+
+// A beam emitter and the number of timelines it starts
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Source {
+    row: i32,
+    col: i32,
+    weight: i64,
+}
+
+// The way a beam is travelling
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Dir {
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
+impl Dir {
+    // (row, col) offset of one step
+    fn delta(self) -> (i32, i32) {
+        match self {
+            Dir::Up => (-1, 0),
+            Dir::Down => (1, 0),
+            Dir::Left => (0, -1),
+            Dir::Right => (0, 1),
+        }
+    }
+
+    fn is_vertical(self) -> bool {
+        matches!(self, Dir::Up | Dir::Down)
+    }
+}
+
+// What a cell does to a beam entering it, see the cell list at the top
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Cell {
+    Empty,
+    SplitVertical,
+    SplitHorizontal,
+    // '/'
+    Mirror,
+    // '\'
+    BackMirror,
+    Absorber,
+    Diode(Dir),
+}
+
+impl Cell {
+    fn parse(ch: char) -> Option<Cell> {
+        match ch {
+            '.' | 'S' => Some(Cell::Empty),
+            '^' | 'v' => Some(Cell::SplitVertical),
+            '<' | '>' => Some(Cell::SplitHorizontal),
+            '/' => Some(Cell::Mirror),
+            '\\' => Some(Cell::BackMirror),
+            '#' => Some(Cell::Absorber),
+            'U' => Some(Cell::Diode(Dir::Up)),
+            'D' => Some(Cell::Diode(Dir::Down)),
+            'L' => Some(Cell::Diode(Dir::Left)),
+            'R' => Some(Cell::Diode(Dir::Right)),
+            _ => None,
+        }
+    }
 }
 
-fn solve(input: &[&str]) -> (i64, i64) {
-    (
-        read_tachyons_part1(&input),
-        read_quantum_tachyons_part2(&input),
-    )
+// A beam is the cell it's about to enter and the way it's travelling, sources start by entering
+// their own S
+type Beam = (i32, i32, Dir);
+
+// Everything a beam entering a cell leads to
+#[derive(Debug, Default)]
+struct Step {
+    // Beams that carry on
+    beams: Vec<Beam>,
+    // Timelines that end here, by leaving the grid, being absorbed or blocked
+    ended: i64,
+    // The splitter the beam hit, if it hit one
+    splitter: Option<(i32, i32)>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum ManifoldError {
+    // Unknown character in the grid, row and col are 0-based
+    UnknownCell { row: usize, col: usize, ch: char },
+    // --weights doesn't have one weight per S
+    WeightCount { weights: usize, sources: usize },
+    NegativeWeight { weight: i64 },
+    // A finite timeline count (weights included) past i64::MAX
+    Overflow,
+}
+
+impl fmt::Display for ManifoldError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ManifoldError::UnknownCell { row, col, ch } => {
+                write!(f, "row {} col {}: unknown cell {:?}", row, col, ch)
+            }
+            ManifoldError::WeightCount { weights, sources } => {
+                write!(f, "{} weights given for {} sources", weights, sources)
+            }
+            ManifoldError::NegativeWeight { weight } => {
+                write!(f, "weight {} is negative", weight)
+            }
+            ManifoldError::Overflow => write!(f, "timeline count overflows i64"),
+        }
+    }
+}
+
+// Number of timelines, infinite when a beam can go round a loop with a splitter in it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Timelines {
+    Finite(i64),
+    Infinite,
+}
+
+// Both checked, infinite stays infinite whatever it's combined with
+impl Timelines {
+    fn add(self, other: Timelines) -> Result<Timelines, ManifoldError> {
+        match (self, other) {
+            (Timelines::Finite(a), Timelines::Finite(b)) => a
+                .checked_add(b)
+                .map(Timelines::Finite)
+                .ok_or(ManifoldError::Overflow),
+            _ => Ok(Timelines::Infinite),
+        }
+    }
+
+    fn scale(self, weight: i64) -> Result<Timelines, ManifoldError> {
+        match self {
+            Timelines::Finite(n) => n
+                .checked_mul(weight)
+                .map(Timelines::Finite)
+                .ok_or(ManifoldError::Overflow),
+            Timelines::Infinite => Ok(Timelines::Infinite),
+        }
+    }
+}
+
+impl fmt::Display for Timelines {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Timelines::Finite(n) => write!(f, "{}", n),
+            Timelines::Infinite => write!(f, "inf"),
+        }
+    }
+}
+
+// A beam entering cell (r, c) travelling in `dir`, apply whatever is there
+// A split enters both side cells here too, with `side` set so a splitter there is taken side-on
+// rather than splitting again
+fn enter(grid: &[Vec<Cell>], (r, c, dir): Beam, side: bool) -> Step {
+    let h = grid.len() as i32;
+    let w = grid[0].len() as i32;
+
+    let mut out = Step::default();
+    if r < 0 || r >= h || c < 0 || c >= w {
+        // Beam exits the manifold
+        out.ended = 1;
+        return out;
+    }
+
+    // Carry on into the next cell in direction `d`
+    let ahead = |d: Dir| {
+        let (dr, dc) = d.delta();
+        vec![(r + dr, c + dc, d)]
+    };
+    let cell = grid[r as usize][c as usize];
+    match (cell, dir) {
+        (Cell::Empty, _) => out.beams = ahead(dir),
+        (Cell::SplitVertical, d) | (Cell::SplitHorizontal, d)
+            if !side && (cell == Cell::SplitVertical) == d.is_vertical() =>
+        {
+            out.splitter = Some((r, c));
+            // Sideways for a vertical beam, up and down for a horizontal one, a side off the grid
+            // ends when it's entered
+            let sides = if d.is_vertical() {
+                [(r, c - 1, d), (r, c + 1, d)]
+            } else {
+                [(r - 1, c, d), (r + 1, c, d)]
+            };
+            for beam in sides {
+                let side = enter(grid, beam, true);
+                out.beams.extend(side.beams);
+                out.ended += side.ended;
+            }
+        }
+        // Splitter side-on, the beam passes through
+        (Cell::SplitVertical, _) | (Cell::SplitHorizontal, _) => out.beams = ahead(dir),
+        (Cell::Mirror, d) => {
+            out.beams = ahead(match d {
+                Dir::Right => Dir::Up,
+                Dir::Up => Dir::Right,
+                Dir::Left => Dir::Down,
+                Dir::Down => Dir::Left,
+            })
+        }
+        (Cell::BackMirror, d) => {
+            out.beams = ahead(match d {
+                Dir::Right => Dir::Down,
+                Dir::Down => Dir::Right,
+                Dir::Left => Dir::Up,
+                Dir::Up => Dir::Left,
+            })
+        }
+        (Cell::Diode(allowed), d) if allowed == d => out.beams = ahead(dir),
+        (Cell::Diode(_), _) | (Cell::Absorber, _) => out.ended = 1,
+    }
+    out
 }
 
 // Part 1
 // This was a bit difficult because of the merging of beams
 // Turns the input into a grid and does BFS over beam positions
-// Complexity: O(n) we only process each cell in the grid once
-fn read_tachyons_part1(input: &[&str]) -> i64 {
-    let (grid, (sr, sc)) = parse_grid(input);
+// Every active source seeds the BFS, beams from different sources merge like any other beams
+// A beam is a position and a direction now, so a splitter can be reached more than one way and the
+// splitters hit are kept in a set
+// Complexity: O(n) we only process each cell in the grid once per direction
+fn read_tachyons_part1(grid: &[Vec<Cell>], sources: &[Source]) -> i64 {
     if grid.is_empty() {
         return 0;
     }
 
-    let h = grid.len() as i32;
-    let w = grid[0].len() as i32; // Assuming grid width is fixed
-
-    // BFS over beam positions, merging overlapping beams via visited set
+    // BFS over beams, merging overlapping beams via visited set
     let mut queue = VecDeque::new();
-    let mut visited: HashSet<(i32, i32)> = HashSet::new();
-    let mut sum: i64 = 0;
-
-    queue.push_back((sr, sc));
-    visited.insert((sr, sc));
+    let mut visited: HashSet<Beam> = HashSet::new();
+    let mut splitters: HashSet<(i32, i32)> = HashSet::new();
 
-    while let Some((r, c)) = queue.pop_front() {
-        let nr = r + 1;
-        if nr >= h {
-            // Beam exits the manifold
-            continue;
+    for s in sources.iter().filter(|s| s.weight > 0) {
+        let beam = (s.row, s.col, Dir::Down);
+        if visited.insert(beam) {
+            queue.push_back(beam);
         }
+    }
 
-        let ci = c as usize;
-        let nri = nr as usize;
-        let cell = grid[nri][ci];
-
-        match cell {
-            '.' | 'S' => {
-                // Beam just continues straight down, no split here
-                if visited.insert((nr, c)) {
-                    queue.push_back((nr, c));
-                }
-            }
-            '^' => {
-                // Beam hits a splitter and it stops, and we create left/right beams
-                sum += 1;
-
-                // Left beam from (nr, c - 1)
-                if c > 0 {
-                    let lc = c - 1;
-                    if visited.insert((nr, lc)) {
-                        queue.push_back((nr, lc));
-                    }
-                }
-
-                // Right beam from (nr, c + 1)
-                if c + 1 < w {
-                    let rc = c + 1;
-                    if visited.insert((nr, rc)) {
-                        queue.push_back((nr, rc));
-                    }
-                }
-            }
-            _ => {
-                continue;
+    while let Some(beam) = queue.pop_front() {
+        let next = enter(grid, beam, false);
+        if let Some(pos) = next.splitter {
+            splitters.insert(pos);
+        }
+        for b in next.beams {
+            if visited.insert(b) {
+                queue.push_back(b);
             }
         }
     }
 
-    sum
+    splitters.len() as i64
 }
 
 // Part 2
 // Found this to be quite tricky because it's not number of beams alone anymore but timelines
-// Keep propegating timelines row by row until they exceed the grid
-// Total number of timelines that exit anywhere is the result
-// Complexity: O(n) we only process each cell in the grid once
-fn read_quantum_tachyons_part2(input: &[&str]) -> i64 {
-    let (grid, (sr, sc)) = parse_grid(input);
+// Beams can now travel in any direction, so rather than sweeping row by row this builds the graph
+// of every reachable beam and counts the timelines ending from each one
+// Strongly connected components (Tarjan) find the loops: a loop with any branch in it repeats
+// forever with a way out every time round so it's infinite, a loop with no branch is one timeline
+// Components come out of Tarjan's in reverse topological order, so everything a beam leads to is
+// counted before the beam itself
+// Each source's timelines are scaled by its weight
+// Complexity: O(n) every beam and edge is visited a constant number of times
+fn read_quantum_tachyons_part2(
+    grid: &[Vec<Cell>],
+    sources: &[Source],
+) -> Result<Timelines, ManifoldError> {
     if grid.is_empty() {
-        return 0;
+        return Ok(Timelines::Finite(0));
     }
 
-    let h = grid.len() as i32;
-    let w = grid[0].len() as i32;
+    // Number every reachable beam and record where it goes
+    let mut index: HashMap<Beam, usize> = HashMap::new();
+    let mut next: Vec<Vec<usize>> = Vec::new();
+    let mut ended: Vec<i64> = Vec::new();
+    let mut beams: Vec<Beam> = Vec::new();
+
+    let mut add = |beam: Beam, beams: &mut Vec<Beam>| -> usize {
+        *index.entry(beam).or_insert_with(|| {
+            beams.push(beam);
+            beams.len() - 1
+        })
+    };
+
+    let active: Vec<(usize, i64)> = sources
+        .iter()
+        .filter(|s| s.weight > 0)
+        .map(|s| (add((s.row, s.col, Dir::Down), &mut beams), s.weight))
+        .collect();
+
+    let mut i = 0;
+    while i < beams.len() {
+        let out = enter(grid, beams[i], false);
+        let targets = out.beams.iter().map(|&b| add(b, &mut beams)).collect();
+        next.push(targets);
+        ended.push(out.ended);
+        i += 1;
+    }
+
+    let timelines = count_timelines(&next, &ended)?;
+    active
+        .iter()
+        .try_fold(Timelines::Finite(0), |total, &(b, weight)| {
+            total.add(timelines[b].scale(weight)?)
+        })
+}
+
+// Timelines from every node of the beam graph, `next` are the edges and `ended` the timelines that
+// stop at each node
+// Iterative Tarjan's so a long beam path can't overflow the stack
+fn count_timelines(next: &[Vec<usize>], ended: &[i64]) -> Result<Vec<Timelines>, ManifoldError> {
+    let n = next.len();
+    let mut order: Vec<Option<usize>> = vec![None; n];
+    let mut low: Vec<usize> = vec![0; n];
+    let mut on_stack: Vec<bool> = vec![false; n];
+    let mut stack: Vec<usize> = Vec::new();
+    let mut counter = 0;
 
-    // Map of current beam positions -> number of timelines in that position
-    let mut current: HashMap<(i32, i32), i64> = HashMap::new();
-    current.insert((sr, sc), 1);
+    let mut timelines: Vec<Timelines> = vec![Timelines::Finite(0); n];
 
-    let mut sum: i64 = 0;
+    for root in 0..n {
+        if order[root].is_some() {
+            continue;
+        }
 
-    while !current.is_empty() {
-        let mut next: HashMap<(i32, i32), i64> = HashMap::new();
+        // (node, next edge to look at)
+        let mut work: Vec<(usize, usize)> = vec![(root, 0)];
+        while let Some(&mut (v, ref mut e)) = work.last_mut() {
+            if *e == 0 && order[v].is_none() {
+                order[v] = Some(counter);
+                low[v] = counter;
+                counter += 1;
+                stack.push(v);
+                on_stack[v] = true;
+            }
 
-        for (&(r, c), &count) in current.iter() {
-            let nr = r + 1;
-            if nr >= h {
-                // All these timelines exit the manifold here
-                sum += count;
+            if let Some(&u) = next[v].get(*e) {
+                *e += 1;
+                match order[u] {
+                    None => work.push((u, 0)),
+                    Some(o) if on_stack[u] => low[v] = low[v].min(o),
+                    Some(_) => {}
+                }
                 continue;
             }
 
-            let ci = c as usize;
-            let nri = nr as usize;
-            let cell = grid[nri][ci];
+            work.pop();
+            if let Some(&(parent, _)) = work.last() {
+                low[parent] = low[parent].min(low[v]);
+            }
+            if Some(low[v]) != order[v] {
+                continue;
+            }
 
-            match cell {
-                '.' | 'S' => {
-                    // All timelines continue straight down
-                    *next.entry((nr, c)).or_insert(0) += count;
-                }
-                '^' => {
-                    // Split L R for the timeline
-                    // Left branch
-                    if c > 0 {
-                        let lc = c - 1;
-                        *next.entry((nr, lc)).or_insert(0) += count;
-                    } else {
-                        // Left would fall outside
-                        sum += count;
-                    }
-
-                    // Right branch
-                    if c + 1 < w {
-                        let rc = c + 1;
-                        *next.entry((nr, rc)).or_insert(0) += count;
-                    } else {
-                        // Right would fall outside
-                        sum += count;
-                    }
-                }
-                _ => {
-                    // End
-                    sum += count;
+            // v is the root of a component, pop it off
+            let mut component = Vec::new();
+            loop {
+                let u = stack.pop().unwrap();
+                on_stack[u] = false;
+                component.push(u);
+                if u == v {
+                    break;
                 }
             }
-        }
 
-        current = next;
+            let cyclic = component.len() > 1 || next[v].contains(&v);
+            let value = if !cyclic {
+                next[v]
+                    .iter()
+                    .try_fold(Timelines::Finite(ended[v]), |t, &u| t.add(timelines[u]))?
+            } else if component
+                .iter()
+                .all(|&u| next[u].len() == 1 && ended[u] == 0)
+            {
+                // A plain loop, the beam goes round forever
+                Timelines::Finite(1)
+            } else {
+                Timelines::Infinite
+            };
+            for u in component {
+                timelines[u] = value;
+            }
+        }
     }
 
-    sum
+    Ok(timelines)
 }
 
 // Parse the input as grid to make it easier to find beam and splitter positions
-// Returns the grid and the starting point (S), assuming S is always on the first row
-fn parse_grid(input: &[&str]) -> (Vec<Vec<char>>, (i32, i32)) {
-    let grid: Vec<Vec<char>> = input.iter().map(|l| l.chars().collect()).collect();
+// Returns the grid and every source (S) in reading order, weighted by `weights` if given
+// Short lines are padded with empty cells
+fn parse_grid(
+    input: &[&str],
+    weights: Option<&[i64]>,
+) -> Result<(Vec<Vec<Cell>>, Vec<Source>), ManifoldError> {
+    let width = input.iter().map(|l| l.chars().count()).max().unwrap_or(0);
 
-    let start_col = grid[0]
-        .iter()
-        .position(|&ch| ch == 'S')
-        .expect("S not found in first row") as i32;
+    let mut grid: Vec<Vec<Cell>> = Vec::new();
+    let mut sources: Vec<Source> = Vec::new();
+    for (r, line) in input.iter().enumerate() {
+        let mut row = Vec::with_capacity(width);
+        for (c, ch) in line.chars().enumerate() {
+            let cell = Cell::parse(ch).ok_or(ManifoldError::UnknownCell { row: r, col: c, ch })?;
+            if ch == 'S' {
+                sources.push(Source {
+                    row: r as i32,
+                    col: c as i32,
+                    weight: 1,
+                });
+            }
+            row.push(cell);
+        }
+        row.resize(width, Cell::Empty);
+        grid.push(row);
+    }
+
+    if let Some(weights) = weights {
+        if weights.len() != sources.len() {
+            return Err(ManifoldError::WeightCount {
+                weights: weights.len(),
+                sources: sources.len(),
+            });
+        }
+        for (s, &weight) in sources.iter_mut().zip(weights) {
+            if weight < 0 {
+                return Err(ManifoldError::NegativeWeight { weight });
+            }
+            s.weight = weight;
+        }
+    }
 
-    (grid, (0, start_col))
+    Ok((grid, sources))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn adjacent_splitters_give_finite_timelines() {
+        let cases: [(&[&str], (i64, Timelines)); 4] = [
+            // The beam splits into a splitter beside it, which is passed through side-on
+            (&[".S..", ".^^.", "...."], (1, Timelines::Finite(2))),
+            (&["..S..", ".^^^.", "....."], (1, Timelines::Finite(2))),
+            // Side cells still absorb or turn the split beams
+            (
+                &["..S..", ".#^#.", ".^.^.", "....."],
+                (1, Timelines::Finite(2)),
+            ),
+            (&["..S..", "./^\\.", "....."], (1, Timelines::Finite(2))),
+        ];
+
+        for (grid, expected) in cases {
+            assert_eq!(solve(grid, None), Ok(expected), "{:?}", grid);
+        }
+    }
 }
@@ -21,16 +21,58 @@ pub fn parse_input_as_nums(input: &[&str]) -> Vec<Vec<i64>> {
         .collect()
 }
 
-// Read input into vec split by comma
-pub fn read_csv(input: &str) -> Vec<(i64, i64)> {
+// Merge (start, end) inclusive ranges into sorted, disjoint ranges
+// Overlapping and adjacent ranges are joined, e.g. 1-3,4-6 -> 1-6
+pub fn merge_ranges<T>(input: &[(T, T)]) -> Vec<(T, T)>
+where
+    T: Copy + Ord + std::ops::Sub<Output = T> + From<u8>,
+{
+    let mut ranges = input.to_vec();
+    ranges.sort_unstable();
+
+    let mut merged: Vec<(T, T)> = Vec::with_capacity(ranges.len());
+    for (s, e) in ranges {
+        if let Some(last) = merged.last_mut() {
+            // `s - 1` is only evaluated when s > last.1 so it can't overflow, even for signed bounds
+            if s <= last.1 || s - T::from(1) == last.1 {
+                if e > last.1 {
+                    last.1 = e;
+                }
+                continue;
+            }
+        }
+        merged.push((s, e));
+    }
+    merged
+}
+
+// Read input into vec split by comma, with each bound written in the given radix (2-36)
+// Panics naming the range and radix if a range isn't two numbers in that radix
+pub fn read_csv_radix(input: &str, radix: u32) -> Vec<(u128, u128)> {
     input
         .split(',')
         .map(|part| {
-            let parts: Vec<_> = part.split('-').collect();
-            (
-                parts[0].trim().parse::<i64>().unwrap(),
-                parts[1].trim().parse::<i64>().unwrap(),
-            )
+            let bound = |token: Option<&str>| {
+                token
+                    .and_then(|t| u128::from_str_radix(t.trim(), radix).ok())
+                    .unwrap_or_else(|| {
+                        panic!("{:?} is not a range of base {} numbers", part.trim(), radix)
+                    })
+            };
+            let mut parts = part.split('-');
+            (bound(parts.next()), bound(parts.next()))
         })
         .collect()
 }
+
+// Seeded xorshift for the bins' randomised tests, so every run sees the same cases
+// Not #[cfg(test)]: the bins' test builds link the normal library, where it wouldn't exist
+pub fn xorshift(seed: u64) -> impl FnMut() -> u64 {
+    let mut state = seed;
+    move || {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        state
+    }
+}